@@ -0,0 +1,42 @@
+//! Benchmarks parsing and both parts of every registered day against its input in
+//! inputs/dayNN.txt, skipping days whose input is absent.
+
+use criterion::{
+    criterion_group, criterion_main, measurement::WallTime, BenchmarkGroup, Criterion,
+};
+
+use aoc2020::runner::{self, Bencher, Day};
+use aoc2020::*;
+
+/// Benches each routine as a function of one criterion group
+struct Group<'c>(BenchmarkGroup<'c, WallTime>);
+
+impl Bencher for Group<'_> {
+    fn bench(&mut self, name: &str, routine: &mut dyn FnMut()) {
+        self.0.bench_function(name, |b| b.iter(&mut *routine));
+    }
+}
+
+fn bench_day(c: &mut Criterion, name: &str, day: &Day, input: &str) {
+    let mut group = Group(c.benchmark_group(name));
+    day.bench(input, &mut group).unwrap();
+    group.0.finish();
+}
+
+fn days(c: &mut Criterion) {
+    for day in runner::DAYS {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(day.default_input_path());
+        match input::read(&path) {
+            Ok(input) => bench_day(c, &format!("day{}", day.number), day, &input),
+            Err(_) => eprintln!("Skipping day {}: {} not found", day.number, path.display()),
+        }
+    }
+}
+
+fn day7_bad_case(c: &mut Criterion) {
+    let input = day7::generate_bad_case_for_non_dynamic_programming_solution();
+    bench_day(c, "day7_bad_case", runner::get_day(7).unwrap(), &input);
+}
+
+criterion_group!(benches, days, day7_bad_case);
+criterion_main!(benches);
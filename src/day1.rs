@@ -1,73 +1,1194 @@
-use anyhow::Error;
-use std::{collections::HashSet, num::ParseIntError};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, HashMap, HashSet},
+    convert::{TryFrom, TryInto},
+    fmt,
+    fs::{self, File, OpenOptions},
+    iter::FromIterator,
+    io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+    sync::atomic::{self, AtomicUsize},
+};
 
-pub fn input(input: &str) -> Result<Vec<u64>, ParseIntError> {
-    input
-        .split_ascii_whitespace()
-        .map(str::parse::<u64>)
-        .collect::<Result<Vec<_>, _>>()
+use crate::{
+    solution::{Answer, Solution},
+    Error, Location,
+};
+
+/// An expense report entry and the 1-based line of the report it's on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Entry {
+    pub value: i64,
+    pub line: usize,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} on line {}", self.value, self.line)
+    }
+}
+
+/// The entries of an expense report, collected from [`Entry`]s so `lines[idx]` is always the line
+/// `numbers[idx]` is on
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    numbers: Vec<i64>,
+    lines: Vec<usize>,
+}
+
+impl Report {
+    /// The value of each entry, in the order they appear
+    pub fn numbers(&self) -> &[i64] {
+        &self.numbers
+    }
+
+    /// The line each entry is on, matching up with `numbers`
+    pub fn lines(&self) -> &[usize] {
+        &self.lines
+    }
+
+    /// The entries at `indices`
+    pub fn entries(&self, indices: &[usize]) -> Vec<Entry> {
+        indices
+            .iter()
+            .map(|&idx| Entry {
+                value: self.numbers[idx],
+                line: self.lines[idx],
+            })
+            .collect()
+    }
+}
+
+impl FromIterator<Entry> for Report {
+    fn from_iter<I: IntoIterator<Item = Entry>>(entries: I) -> Self {
+        let (numbers, lines) = entries
+            .into_iter()
+            .map(|entry| (entry.value, entry.line))
+            .unzip();
+        Report { numbers, lines }
+    }
+}
+
+/// Entries chosen from a report and their product, which is the puzzle's answer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Match {
+    pub entries: Vec<Entry>,
+    pub product: i64,
 }
 
-pub fn part1(numbers: &[u64]) -> Result<(), Error> {
-    let mut number_set = HashSet::new();
-    for number in numbers {
-        let complement = 2020 - number;
-        if number_set.contains(&complement) {
-            println!("{} * {} = {}", number, complement, number * complement);
-            return Ok(());
-        } else {
-            number_set.insert(number);
+impl Match {
+    pub fn new(entries: Vec<Entry>) -> Result<Self, Error> {
+        let product = entries
+            .iter()
+            .try_fold(1i64, |product, entry| product.checked_mul(entry.value))
+            .ok_or_else(|| Error::Day1Overflow {
+                entries: entries.clone(),
+            })?;
+        Ok(Match { entries, product })
+    }
+}
+
+impl Answer for Match {
+    fn render(&self) -> Option<String> {
+        Some(self.product.to_string())
+    }
+
+    fn detail(&self) -> Option<String> {
+        Some(describe(&self.entries))
+    }
+}
+
+/// Lists entries with their lines, e.g. "1721 on line 1, 299 on line 4"
+pub fn describe(entries: &[Entry]) -> String {
+    entries
+        .iter()
+        .map(Entry::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn input(input: &str) -> Result<Report, Error> {
+    entries(input.as_bytes()).collect()
+}
+
+/// The sum the expense report entries need to add up to
+pub const TARGET: i64 = 2020;
+
+/// Finds `k` entries at distinct positions in `numbers` summing to `target`,
+/// returned in the order they appear
+pub fn k_sum(numbers: &[i64], k: usize, target: i64) -> Option<Vec<i64>> {
+    choose(numbers, Mode::KSum(k), target)
+}
+
+/// Iterates over every combination of `k` entries at distinct positions in `numbers` summing to
+/// `target`, each as ascending indices into `numbers`.
+///
+/// Sums are accumulated as `i128`, so no combination of `i64` entries can overflow.
+pub fn k_sums(numbers: &[i64], k: usize, target: i64) -> KSums<'_> {
+    let mut positions = HashMap::<i64, Vec<usize>>::new();
+    for (idx, &number) in numbers.iter().enumerate() {
+        positions.entry(number).or_default().push(idx);
+    }
+
+    KSums {
+        numbers,
+        k,
+        target: target.into(),
+        min_number: numbers.iter().min().cloned().unwrap_or(0).into(),
+        positions,
+        prefix: Vec::new(),
+        prefix_sums: Vec::new(),
+        next_idx: 0,
+        prefix_yielded: false,
+        candidates: None,
+        seen_values: None,
+        exhausted: false,
+    }
+}
+
+/// Iterator returned by [`k_sums`].
+///
+/// Walks every ascending choice of the first `k - 1` indices depth first, skipping choices that
+/// overshoot the target even if every remaining entry were the smallest one, then looks up the
+/// entry completing the sum by value.
+pub struct KSums<'a> {
+    numbers: &'a [i64],
+    k: usize,
+    target: i128,
+    min_number: i128,
+    /// Indices of each value in `numbers`, ascending
+    positions: HashMap<i64, Vec<usize>>,
+    prefix: Vec<usize>,
+    prefix_sums: Vec<i128>,
+    /// The next index to try extending `prefix` with
+    next_idx: usize,
+    prefix_yielded: bool,
+    /// The value completing the current prefix and how far through its positions we are
+    candidates: Option<(i64, usize)>,
+    seen_values: Option<HashSet<Vec<i64>>>,
+    exhausted: bool,
+}
+
+impl<'a> KSums<'a> {
+    /// Only yields the first combination found for each set of values, so inputs with
+    /// repeated entries don't report the same sum once per repeat
+    pub fn dedup_values(mut self) -> Self {
+        self.seen_values = Some(HashSet::new());
+        self
+    }
+
+    fn prefix_sum(&self) -> i128 {
+        self.prefix_sums.last().cloned().unwrap_or(0)
+    }
+
+    /// Moves on to the next prefix of `k - 1` indices, returning None once there are no more
+    fn advance_prefix(&mut self) -> Option<()> {
+        loop {
+            if self.prefix.len() == self.k - 1 && !self.prefix_yielded {
+                self.prefix_yielded = true;
+                return Some(());
+            }
+
+            if self.prefix.len() == self.k - 1 || self.next_idx >= self.numbers.len() {
+                self.next_idx = self.prefix.pop()? + 1;
+                self.prefix_sums.pop();
+                self.prefix_yielded = false;
+                continue;
+            }
+
+            let idx = self.next_idx;
+            self.next_idx += 1;
+            let sum = self.prefix_sum() + i128::from(self.numbers[idx]);
+            let still_needed = (self.k - self.prefix.len() - 1) as i128;
+            if sum + still_needed * self.min_number <= self.target {
+                self.prefix.push(idx);
+                self.prefix_sums.push(sum);
+            }
+        }
+    }
+
+    fn next_combination(&mut self) -> Option<Vec<usize>> {
+        if self.k == 0 {
+            // The empty combination sums to 0, and is the only combination there is
+            if self.exhausted || self.target != 0 {
+                return None;
+            }
+            self.exhausted = true;
+            return Some(Vec::new());
+        }
+
+        loop {
+            if let Some((value, cursor)) = &mut self.candidates {
+                if let Some(&idx) = self.positions[value].get(*cursor) {
+                    *cursor += 1;
+                    let mut combination = self.prefix.clone();
+                    combination.push(idx);
+                    return Some(combination);
+                }
+                self.candidates = None;
+            }
+
+            self.advance_prefix()?;
+            let remaining = match i64::try_from(self.target - self.prefix_sum()) {
+                Ok(remaining) => remaining,
+                Err(_) => continue,
+            };
+            if let Some(positions) = self.positions.get(&remaining) {
+                let after = self.prefix.last().map(|&idx| idx + 1).unwrap_or(0);
+                let cursor = positions.partition_point(|&idx| idx < after);
+                self.candidates = Some((remaining, cursor));
+            }
         }
     }
+}
+
+impl<'a> Iterator for KSums<'a> {
+    type Item = Vec<usize>;
 
-    println!("2-sum not found");
+    fn next(&mut self) -> Option<Self::Item> {
+        let numbers = self.numbers;
+        loop {
+            let combination = self.next_combination()?;
+            let seen_values = match &mut self.seen_values {
+                Some(seen_values) => seen_values,
+                None => return Some(combination),
+            };
 
-    Ok(())
+            let mut values = combination
+                .iter()
+                .map(|&idx| numbers[idx])
+                .collect::<Vec<_>>();
+            values.sort_unstable();
+            if seen_values.insert(values) {
+                return Some(combination);
+            }
+        }
+    }
+}
+
+/// Any number of entries summing exactly to `target`, in the order they appear.
+///
+/// A pseudo-polynomial DP over every reachable sum, so time and memory grow with the spread
+/// between the smallest and largest possible sums rather than with the number of subsets.
+/// The empty subset is the answer for a target of 0. Gives up with None, without allocating,
+/// when the spread is wider than [`MAX_SUBSET_SUM_SPREAD`].
+pub fn subset_sum(numbers: &[i64], target: i64) -> Option<Vec<i64>> {
+    choose(numbers, Mode::SubsetSum, target)
 }
 
-pub fn part2(numbers: &[u64]) -> Result<(), Error> {
-    const TARGET: u64 = 2020;
+/// The most sums `subset_sum` will track, which bounds its tables to around 70MB
+pub const MAX_SUBSET_SUM_SPREAD: usize = 1 << 22;
 
-    let sorted_numbers = {
-        let mut copy = numbers.to_vec();
-        copy.sort();
-        copy
-    };
+fn subset_sum_indices(numbers: &[i64], target: i64) -> Option<Vec<usize>> {
+    let target = i128::from(target);
+    let mut lowest: i128 = numbers.iter().filter(|&&n| n < 0).map(|&n| i128::from(n)).sum();
+    let mut highest: i128 = numbers.iter().filter(|&&n| n > 0).map(|&n| i128::from(n)).sum();
+    // With no negative entries a sum above the target can't come back down, and vice versa
+    if lowest == 0 {
+        highest = highest.min(target);
+    }
+    if highest == 0 {
+        lowest = lowest.max(target);
+    }
+    if target < lowest || target > highest {
+        return None;
+    }
+
+    // The entry whose inclusion first reached each sum; the sum without it was reached using
+    // only earlier entries, so following these back never uses an entry twice
+    let spread = usize::try_from(highest - lowest + 1).ok()?;
+    if spread > MAX_SUBSET_SUM_SPREAD {
+        return None;
+    }
+    let mut reached_by = vec![None; spread];
+    let slot = |sum: i128| (sum - lowest) as usize;
+    let mut reached = vec![false; reached_by.len()];
+    reached[slot(0)] = true;
 
-    for i in 0..sorted_numbers.len() - 2 {
-        let first_number = sorted_numbers[i];
-        if first_number >= TARGET {
+    for (idx, &number) in numbers.iter().enumerate() {
+        let number = i128::from(number);
+        if number == 0 {
             continue;
         }
 
-        let mut search_window = &sorted_numbers[i + 1..sorted_numbers.len()];
-        while search_window.len() > 1 {
-            let second_number = search_window[0];
-            let third_number = search_window[search_window.len() - 1];
+        // Visit sums moving away from `number` so each entry is only added once per sum
+        for offset in 0..reached.len() as i128 {
+            let sum = if number > 0 {
+                highest - offset
+            } else {
+                lowest + offset
+            };
+            let without = sum - number;
+            if !reached[slot(sum)] && lowest <= without && without <= highest && reached[slot(without)]
+            {
+                reached[slot(sum)] = true;
+                reached_by[slot(sum)] = Some(idx);
+            }
+        }
+    }
+
+    if !reached[slot(target)] {
+        return None;
+    }
+
+    let mut indices = Vec::new();
+    let mut sum = target;
+    while let Some(idx) = reached_by[slot(sum)] {
+        indices.push(idx);
+        sum -= i128::from(numbers[idx]);
+    }
+    indices.reverse();
+    Some(indices)
+}
+
+/// `k` entries at distinct positions whose sum is as close to `target` as possible without
+/// exceeding it, in the order they appear
+pub fn closest_sum(numbers: &[i64], k: usize, target: i64) -> Option<Vec<i64>> {
+    choose(numbers, Mode::ClosestSum(k), target)
+}
+
+fn closest_sum_indices(numbers: &[i64], k: usize, target: i64) -> Option<Vec<usize>> {
+    let mut sorted = numbers.iter().cloned().enumerate().collect::<Vec<_>>();
+    sorted.sort_unstable_by_key(|&(_, number)| number);
+    let values = sorted
+        .iter()
+        .map(|&(_, number)| i128::from(number))
+        .collect::<Vec<_>>();
+
+    let (_, positions) = closest_sum_from(&values, 0, k, i128::from(target))?;
+    let mut indices = positions
+        .into_iter()
+        .map(|position| sorted[position].0)
+        .collect::<Vec<_>>();
+    indices.sort_unstable();
+    Some(indices)
+}
+
+/// The best sum of `k` of the ascending `values[start..]` that doesn't exceed `target`, and the
+/// positions in `values` making it up.
+///
+/// Fixes each smallest entry in turn and recurses, down to a two-pointer walk for the last two.
+fn closest_sum_from(
+    values: &[i128],
+    start: usize,
+    k: usize,
+    target: i128,
+) -> Option<(i128, Vec<usize>)> {
+    let values_left = values.len().saturating_sub(start);
+    if k > values_left {
+        return None;
+    }
 
-            match (first_number + second_number + third_number).cmp(&TARGET) {
-                std::cmp::Ordering::Less => {
-                    search_window = &search_window[1..];
+    match k {
+        0 if target >= 0 => Some((0, Vec::new())),
+        0 => None,
+        1 => {
+            let end = start + values[start..].partition_point(|&value| value <= target);
+            if end == start {
+                None
+            } else {
+                Some((values[end - 1], vec![end - 1]))
+            }
+        }
+        2 => {
+            let mut best: Option<(i128, Vec<usize>)> = None;
+            let (mut low, mut high) = (start, values.len() - 1);
+            while low < high {
+                let sum = values[low] + values[high];
+                if sum > target {
+                    high -= 1;
+                    continue;
+                }
+
+                if best.as_ref().is_none_or(|(best_sum, _)| sum > *best_sum) {
+                    best = Some((sum, vec![low, high]));
+                }
+                if sum == target {
+                    break;
                 }
-                std::cmp::Ordering::Equal => {
-                    println!(
-                        "{} * {} * {} = {}",
-                        first_number,
-                        second_number,
-                        third_number,
-                        first_number * second_number * third_number
-                    );
-
-                    return Ok(());
+                low += 1;
+            }
+            best
+        }
+        _ => {
+            let mut best: Option<(i128, Vec<usize>)> = None;
+            for first in start..=values.len() - k {
+                // Values only grow from here, so once the smallest choice overshoots, all will
+                let smallest: i128 = values[first..first + k].iter().sum();
+                if smallest > target {
+                    break;
+                }
+
+                let rest = closest_sum_from(values, first + 1, k - 1, target - values[first]);
+                if let Some((rest_sum, mut positions)) = rest {
+                    let sum = values[first] + rest_sum;
+                    if best.as_ref().is_none_or(|(best_sum, _)| sum > *best_sum) {
+                        positions.insert(0, first);
+                        best = Some((sum, positions));
+                    }
+                    if sum == target {
+                        break;
+                    }
+                }
+            }
+            best
+        }
+    }
+}
+
+/// The ways of choosing entries from the expense report to match a target
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// `k` entries summing exactly to the target, as parts 1 and 2 use with k of 2 and 3
+    KSum(usize),
+    /// Any number of entries summing exactly to the target
+    SubsetSum,
+    /// `k` entries summing as close to the target as possible without exceeding it
+    ClosestSum(usize),
+}
+
+/// Chooses entries from `numbers` for `target` according to `mode`, in the order they appear
+pub fn choose(numbers: &[i64], mode: Mode, target: i64) -> Option<Vec<i64>> {
+    choose_indices(numbers, mode, target)
+        .map(|indices| indices.into_iter().map(|idx| numbers[idx]).collect())
+}
+
+/// Like [`choose`], but returns the ascending indices of the chosen entries
+pub fn choose_indices(numbers: &[i64], mode: Mode, target: i64) -> Option<Vec<usize>> {
+    match mode {
+        Mode::KSum(k) => k_sums(numbers, k, target).next(),
+        Mode::SubsetSum => subset_sum_indices(numbers, target),
+        Mode::ClosestSum(k) => closest_sum_indices(numbers, k, target),
+    }
+}
+
+/// Finds entries in `report` for `target` according to `mode`, along with their product
+pub fn find(report: &Report, mode: Mode, target: i64) -> Result<Option<Match>, Error> {
+    choose_indices(&report.numbers, mode, target)
+        .map(|indices| Match::new(report.entries(&indices)))
+        .transpose()
+}
+
+/// Reads whitespace separated entries from `reader` as it fills its buffer, holding no more
+/// than one entry at a time however the report is laid out
+pub fn entries<R: BufRead>(reader: R) -> Entries<R> {
+    Entries {
+        reader,
+        next: Location { line: 1, column: 1 },
+        partial: PartialEntry {
+            bytes: Vec::new(),
+            start: Location { line: 1, column: 1 },
+        },
+    }
+}
+
+/// Bytes of an entry kept for parsing, past its leading zeros. Any `i64` fits, so a longer
+/// entry is invalid and reported from what it starts with.
+const MAX_ENTRY_BYTES: usize = 64;
+
+/// The entries of an expense report as they're read, see [`entries`]
+pub struct Entries<R> {
+    reader: R,
+    /// Where the next byte read is
+    next: Location,
+    partial: PartialEntry,
+}
+
+/// The bytes of an entry read so far and where it starts
+struct PartialEntry {
+    bytes: Vec<u8>,
+    start: Location,
+}
+
+impl PartialEntry {
+    fn push(&mut self, byte: u8, at: Location) {
+        if self.bytes.is_empty() {
+            self.start = at;
+        }
+        // Drop leading zeros so a valid entry always fits
+        let sign = match self.bytes.first() {
+            Some(b'-') | Some(b'+') => 1,
+            _ => 0,
+        };
+        if self.bytes[sign..] == b"0"[..] {
+            self.bytes.pop();
+        }
+        if self.bytes.len() < MAX_ENTRY_BYTES {
+            self.bytes.push(byte);
+        }
+    }
+
+    /// Parses the entry read so far, if there is one
+    fn finish(&mut self) -> Option<Result<Entry, Error>> {
+        if self.bytes.is_empty() {
+            return None;
+        }
+
+        let parsed = String::from_utf8_lossy(&self.bytes).parse::<i64>();
+        self.bytes.clear();
+        Some(match parsed {
+            Ok(value) => Ok(Entry {
+                value,
+                line: self.start.line,
+            }),
+            Err(source) => Err(Error::Day1 {
+                location: self.start,
+                source,
+            }),
+        })
+    }
+}
+
+impl<R: BufRead> Iterator for Entries<R> {
+    type Item = Result<Entry, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let buffer = match self.reader.fill_buf() {
+                Ok([]) => return self.partial.finish(),
+                Ok(buffer) => buffer,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(source) => return Some(Err(Error::Day1Io { source })),
+            };
+
+            let mut found = None;
+            let mut used = buffer.len();
+            for (idx, &byte) in buffer.iter().enumerate() {
+                if !byte.is_ascii_whitespace() {
+                    self.partial.push(byte, self.next);
+                    // Columns count characters, so skip UTF-8 continuation bytes
+                    if byte & 0xc0 != 0x80 {
+                        self.next.column += 1;
+                    }
+                    continue;
+                }
+
+                found = self.partial.finish();
+                if byte == b'\n' {
+                    self.next = Location {
+                        line: self.next.line + 1,
+                        column: 1,
+                    };
+                } else {
+                    self.next.column += 1;
+                }
+                if found.is_some() {
+                    used = idx + 1;
+                    break;
+                }
+            }
+            self.reader.consume(used);
+            if found.is_some() {
+                return found;
+            }
+        }
+    }
+}
+
+/// Finds two entries summing to `target` while reading them, stopping as soon as the second
+/// one arrives. Returned in the order they appear.
+///
+/// Only the distinct entries read before the match are kept in memory.
+pub fn stream_two_sum<R: BufRead>(reader: R, target: i64) -> Result<Option<Match>, Error> {
+    let mut seen = HashMap::new();
+    for entry in entries(reader) {
+        let entry = entry?;
+        if let Ok(complement) = i64::try_from(i128::from(target) - i128::from(entry.value)) {
+            if let Some(&line) = seen.get(&complement) {
+                let first = Entry {
+                    value: complement,
+                    line,
+                };
+                return Match::new(vec![first, entry]).map(Some);
+            }
+        }
+        seen.entry(entry.value).or_insert(entry.line);
+    }
+
+    Ok(None)
+}
+
+/// Finds three entries at distinct positions summing to `target` while holding at most
+/// `max_in_memory` of them in memory at once. Returned in ascending order of value.
+///
+/// Reports that don't fit are sorted externally: sorted runs are spilled to the system temp
+/// directory and merged into one sorted file, at most [`MERGE_FAN_IN`] runs at a time, which a
+/// two-pointer walk then reads through a few fixed size blocks.
+pub fn stream_three_sum<R: BufRead>(
+    reader: R,
+    target: i64,
+    max_in_memory: usize,
+) -> Result<Option<Match>, Error> {
+    let max_in_memory = max_in_memory.max(1);
+    let mut chunk = Vec::new();
+    let mut runs = Vec::new();
+    for entry in entries(reader) {
+        chunk.push(entry?);
+        if chunk.len() == max_in_memory {
+            chunk.sort_unstable();
+            let run = SpillFile::write(chunk.drain(..).map(Ok));
+            runs.push(run.map_err(|source| Error::Day1Io { source })?);
+        }
+    }
+
+    let found = if runs.is_empty() {
+        let report = chunk.into_iter().collect::<Report>();
+        k_sums(&report.numbers, 3, target).next().map(|indices| {
+            let mut entries = report.entries(&indices);
+            entries.sort_unstable();
+            entries
+        })
+    } else {
+        external_three_sum(runs, chunk, i128::from(target))
+            .map_err(|source| Error::Day1Io { source })?
+    };
+    found.map(Match::new).transpose()
+}
+
+/// Most runs merged at once, as each holds a file open while it's read
+pub const MERGE_FAN_IN: usize = 64;
+
+/// Spills the last `chunk`, merges the sorted runs in passes until one file is left and walks it
+fn external_three_sum(
+    mut runs: Vec<SpillFile>,
+    mut chunk: Vec<Entry>,
+    target: i128,
+) -> io::Result<Option<Vec<Entry>>> {
+    chunk.sort_unstable();
+    runs.push(SpillFile::write(chunk.drain(..).map(Ok))?);
+    drop(chunk);
+
+    while runs.len() > 1 {
+        runs = runs
+            .chunks(MERGE_FAN_IN)
+            .map(|group| {
+                let readers = group
+                    .iter()
+                    .map(SpillFile::entries)
+                    .collect::<io::Result<Vec<_>>>()?;
+                SpillFile::write(merge(readers)?)
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+    }
+
+    match runs.pop() {
+        Some(sorted) => sorted_three_sum(&sorted, target),
+        None => Ok(None),
+    }
+}
+
+/// Two-pointer 3-sum over the ascending entries of `sorted`
+fn sorted_three_sum(sorted: &SpillFile, target: i128) -> io::Result<Option<Vec<Entry>>> {
+    let (mut first, mut low, mut high) = (sorted.blocks()?, sorted.blocks()?, sorted.blocks()?);
+    for i in 0..sorted.len.saturating_sub(2) {
+        let a = first.get(i)?;
+        // Every later entry is at least `a`, so no sum from here on can come back down
+        if 3 * i128::from(a.value) > target {
+            break;
+        }
+
+        let (mut j, mut k) = (i + 1, sorted.len - 1);
+        while j < k {
+            let (b, c) = (low.get(j)?, high.get(k)?);
+            let sum = i128::from(a.value) + i128::from(b.value) + i128::from(c.value);
+            match sum.cmp(&target) {
+                Ordering::Equal => return Ok(Some(vec![a, b, c])),
+                Ordering::Less => j += 1,
+                Ordering::Greater => k -= 1,
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Merges ascending runs of entries into one ascending sequence
+fn merge<I>(mut runs: Vec<I>) -> io::Result<impl Iterator<Item = io::Result<Entry>>>
+where
+    I: Iterator<Item = io::Result<Entry>>,
+{
+    let mut heap = BinaryHeap::new();
+    for (idx, run) in runs.iter_mut().enumerate() {
+        if let Some(entry) = run.next() {
+            heap.push(Reverse((entry?, idx)));
+        }
+    }
+
+    Ok(std::iter::from_fn(move || {
+        let Reverse((entry, idx)) = heap.pop()?;
+        match runs[idx].next() {
+            Some(Ok(next)) => heap.push(Reverse((next, idx))),
+            Some(Err(e)) => return Some(Err(e)),
+            None => {}
+        }
+        Some(Ok(entry))
+    }))
+}
+
+/// Entries read into memory at once when walking a [`SpillFile`]
+const BLOCK_LEN: u64 = 1024;
+
+/// Bytes per entry in a [`SpillFile`]: the value then the line, both little-endian
+const ENTRY_BYTES: usize = 16;
+
+fn encode(entry: Entry) -> [u8; ENTRY_BYTES] {
+    let mut bytes = [0; ENTRY_BYTES];
+    bytes[..8].copy_from_slice(&entry.value.to_le_bytes());
+    bytes[8..].copy_from_slice(&(entry.line as u64).to_le_bytes());
+    bytes
+}
+
+fn decode(bytes: &[u8]) -> Entry {
+    Entry {
+        value: i64::from_le_bytes(bytes[..8].try_into().unwrap()),
+        line: u64::from_le_bytes(bytes[8..ENTRY_BYTES].try_into().unwrap()) as usize,
+    }
+}
+
+/// A temporary file of entries, removed when dropped
+struct SpillFile {
+    path: PathBuf,
+    len: u64,
+}
+
+impl SpillFile {
+    fn write(entries: impl Iterator<Item = io::Result<Entry>>) -> io::Result<Self> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        let path = std::env::temp_dir().join(format!(
+            "aoc2020-day1-{}-{}.bin",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, atomic::Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new().write(true).create_new(true).open(&path)?;
+        let mut spill = SpillFile { path, len: 0 };
+
+        let mut writer = BufWriter::new(file);
+        for entry in entries {
+            writer.write_all(&encode(entry?))?;
+            spill.len += 1;
+        }
+        writer.flush()?;
+        Ok(spill)
+    }
+
+    /// Reads the entries back in order
+    fn entries(&self) -> io::Result<impl Iterator<Item = io::Result<Entry>>> {
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        Ok((0..self.len).map(move |_| {
+            let mut bytes = [0; ENTRY_BYTES];
+            reader.read_exact(&mut bytes)?;
+            Ok(decode(&bytes))
+        }))
+    }
+
+    fn blocks(&self) -> io::Result<Blocks> {
+        Ok(Blocks {
+            file: File::open(&self.path)?,
+            start: 0,
+            block: Vec::new(),
+        })
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Random access to a [`SpillFile`], keeping the block around the last entry read in memory
+struct Blocks {
+    file: File,
+    start: u64,
+    block: Vec<Entry>,
+}
+
+impl Blocks {
+    fn get(&mut self, idx: u64) -> io::Result<Entry> {
+        if idx < self.start || idx >= self.start + self.block.len() as u64 {
+            self.start = idx - idx % BLOCK_LEN;
+            self.file
+                .seek(SeekFrom::Start(self.start * ENTRY_BYTES as u64))?;
+
+            let mut bytes = Vec::new();
+            (&self.file)
+                .take(BLOCK_LEN * ENTRY_BYTES as u64)
+                .read_to_end(&mut bytes)?;
+            self.block.clear();
+            self.block
+                .extend(bytes.chunks_exact(ENTRY_BYTES).map(decode));
+        }
+
+        self.block
+            .get((idx - self.start) as usize)
+            .cloned()
+            .ok_or_else(|| io::ErrorKind::UnexpectedEof.into())
+    }
+}
+
+/// Returns the two entries summing to 2020 and their product, or None if there aren't any
+pub fn part1(report: &Report) -> Result<Option<Match>, Error> {
+    find(report, Mode::KSum(2), TARGET)
+}
+
+/// Returns the three entries summing to 2020 and their product, or None if there aren't any
+pub fn part2(report: &Report) -> Result<Option<Match>, Error> {
+    find(report, Mode::KSum(3), TARGET)
+}
+
+pub struct Day1;
+
+impl<'a> Solution<'a> for Day1 {
+    type Input = Report;
+    type Answer1 = Option<Match>;
+    type Answer2 = Option<Match>;
+
+    fn parse(raw: &'a str) -> Result<Self::Input, Error> {
+        input(raw)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: [i64; 6] = [1721, 979, 366, 299, 675, 1456];
+
+    /// A report with each of `numbers` on its own line
+    fn report(numbers: &[i64]) -> Report {
+        numbers
+            .iter()
+            .zip(1..)
+            .map(|(&value, line)| Entry { value, line })
+            .collect()
+    }
+
+    fn product(found: Result<Option<Match>, Error>) -> Option<i64> {
+        found.unwrap().map(|found| found.product)
+    }
+
+    #[test]
+    fn example_test() {
+        assert_eq!(product(part1(&report(&EXAMPLE))), Some(514579));
+        assert_eq!(product(part2(&report(&EXAMPLE))), Some(241861950));
+    }
+
+    #[test]
+    fn line_test() {
+        let report = input("1721 979
+366
+
+299
+675 1456
+").unwrap();
+        let found = part1(&report).unwrap().unwrap();
+        assert_eq!(
+            found.entries,
+            vec![
+                Entry {
+                    value: 1721,
+                    line: 1
+                },
+                Entry {
+                    value: 299,
+                    line: 4
                 }
-                std::cmp::Ordering::Greater => {
-                    search_window = &search_window[..search_window.len() - 1]
+            ]
+        );
+        assert_eq!(found.render().as_deref(), Some("514579"));
+        assert_eq!(
+            found.detail().as_deref(),
+            Some("1721 on line 1, 299 on line 4")
+        );
+
+        let lines = |found: Match| found.entries.iter().map(|e| e.line).collect::<Vec<_>>();
+        assert_eq!(lines(part2(&report).unwrap().unwrap()), vec![1, 2, 5]);
+    }
+
+    #[test]
+    fn short_input_test() {
+        assert_eq!(product(part1(&report(&[]))), None);
+        assert_eq!(product(part1(&report(&[2020]))), None);
+        assert_eq!(product(part2(&report(&[]))), None);
+        assert_eq!(product(part2(&report(&[1000, 1020]))), None);
+        assert_eq!(product(part2(&report(&[3000, 20]))), None);
+    }
+
+    #[test]
+    fn signed_test() {
+        assert_eq!(
+            product(part1(&report(&[3000, 5, -980]))),
+            Some(3000 * -980)
+        );
+        assert_eq!(
+            product(part2(&report(&[-10, 4000, 7, -1970]))),
+            Some(-10 * 4000 * -1970)
+        );
+        assert_eq!(k_sum(&[-3, -2, -1], 2, -5), Some(vec![-3, -2]));
+        assert_eq!(k_sum(&[-3, -2, -1], 3, -5), None);
+    }
+
+    #[test]
+    fn overflow_test() {
+        // The sum overflows i64 part way through, but not by the end
+        assert_eq!(
+            k_sum(&[i64::MAX, i64::MAX, i64::MIN, 1], 3, i64::MAX - 1),
+            Some(vec![i64::MAX, i64::MAX, i64::MIN])
+        );
+        assert_eq!(k_sum(&[i64::MAX, 1], 2, i64::MIN), None);
+
+        let error = part1(&input("7\n1099511627776\n-1099511625756\n").unwrap()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Day 1: the product of 1099511627776 on line 2, -1099511625756 on line 3 overflows"
+        );
+    }
+
+    #[test]
+    fn k_sum_test() {
+        assert_eq!(k_sum(&EXAMPLE, 0, 0), Some(vec![]));
+        assert_eq!(k_sum(&EXAMPLE, 1, 366), Some(vec![366]));
+        assert_eq!(k_sum(&EXAMPLE, 2, 2020), Some(vec![1721, 299]));
+        assert_eq!(k_sum(&EXAMPLE, 3, 2020), Some(vec![979, 366, 675]));
+        assert_eq!(k_sum(&EXAMPLE, 4, 979 + 366 + 299 + 1456), Some(vec![979, 366, 299, 1456]));
+        assert_eq!(k_sum(&EXAMPLE, 7, 0), None);
+        assert_eq!(k_sum(&EXAMPLE, 2, 1), None);
+
+        // Each entry can only be used once, even when a value repeats
+        assert_eq!(k_sum(&[1010, 5], 2, 2020), None);
+        assert_eq!(k_sum(&[1010, 5, 1010], 2, 2020), Some(vec![1010, 1010]));
+    }
+
+    #[test]
+    fn k_sums_test() {
+        let numbers = [1, 2, 3, 4, 5];
+        assert_eq!(
+            k_sums(&numbers, 2, 6).collect::<Vec<_>>(),
+            vec![vec![0, 4], vec![1, 3]]
+        );
+        assert_eq!(
+            k_sums(&numbers, 3, 9).collect::<Vec<_>>(),
+            vec![vec![0, 2, 4], vec![1, 2, 3]]
+        );
+        assert_eq!(k_sums(&numbers, 1, 3).collect::<Vec<_>>(), vec![vec![2]]);
+        assert_eq!(k_sums(&numbers, 0, 0).collect::<Vec<_>>(), vec![vec![]]);
+        assert_eq!(k_sums(&numbers, 0, 1).count(), 0);
+        assert_eq!(k_sums(&numbers, 6, 15).count(), 0);
+        assert_eq!(k_sums(&numbers, 5, 15).count(), 1);
+        assert_eq!(k_sums(&[], 2, 0).count(), 0);
+    }
+
+    #[test]
+    fn k_sums_duplicates_test() {
+        // Every pair of positions is its own combination...
+        let numbers = [5, 5, 5, 0];
+        assert_eq!(
+            k_sums(&numbers, 2, 10).collect::<Vec<_>>(),
+            vec![vec![0, 1], vec![0, 2], vec![1, 2]]
+        );
+        assert_eq!(k_sums(&numbers, 3, 10).count(), 3);
+
+        // ...unless they're deduplicated by value
+        assert_eq!(
+            k_sums(&numbers, 2, 10).dedup_values().collect::<Vec<_>>(),
+            vec![vec![0, 1]]
+        );
+        assert_eq!(
+            k_sums(&[1, 3, 1, 3, 2, 2], 2, 4)
+                .dedup_values()
+                .collect::<Vec<_>>(),
+            vec![vec![0, 1], vec![4, 5]]
+        );
+    }
+
+    /// Every combination of `k` indices into a list of `len` entries
+    fn combinations(len: usize, k: usize) -> Vec<Vec<usize>> {
+        if k == 0 {
+            return vec![vec![]];
+        }
+        (0..len)
+            .flat_map(|last| {
+                combinations(last, k - 1).into_iter().map(move |mut c| {
+                    c.push(last);
+                    c
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn subset_sum_test() {
+        let chosen = subset_sum(&EXAMPLE, 1721 + 366 + 1456).unwrap();
+        assert_eq!(chosen.iter().sum::<i64>(), 1721 + 366 + 1456);
+        assert_eq!(subset_sum(&EXAMPLE, 2020), Some(vec![1721, 299]));
+        assert_eq!(subset_sum(&EXAMPLE, 0), Some(vec![]));
+        assert_eq!(subset_sum(&EXAMPLE, 1), None);
+        assert_eq!(subset_sum(&EXAMPLE, EXAMPLE.iter().sum()), Some(EXAMPLE.to_vec()));
+        assert_eq!(subset_sum(&[], 5), None);
+
+        // Every entry is used at most once
+        assert_eq!(subset_sum(&[5], 10), None);
+        assert_eq!(subset_sum(&[5, 0, 5], 10), Some(vec![5, 5]));
+
+        // Negative entries can bring a sum back down
+        assert_eq!(subset_sum(&[7, 9, -4], 5), Some(vec![9, -4]));
+        assert_eq!(subset_sum(&[-3, -8, 2], -6), Some(vec![-8, 2]));
+        assert_eq!(subset_sum(&[-3, -8], 1), None);
+
+        // Spreads too wide to tabulate give up rather than panicking or allocating
+        assert_eq!(subset_sum(&[i64::MIN, i64::MAX], 5), None);
+        assert_eq!(subset_sum(&[i64::MIN, i64::MAX], -1), None);
+        assert_eq!(subset_sum(&[1 << 40, -(1 << 40)], 0), None);
+        assert_eq!(subset_sum(&[i64::MAX, i64::MAX, 1], i64::MAX), None);
+        assert_eq!(
+            subset_sum(&[(MAX_SUBSET_SUM_SPREAD - 1) as i64, 3], 3),
+            Some(vec![3])
+        );
+    }
+
+    #[test]
+    fn closest_sum_test() {
+        assert_eq!(closest_sum(&EXAMPLE, 2, 2020), Some(vec![1721, 299]));
+        assert_eq!(closest_sum(&EXAMPLE, 2, 2019), Some(vec![366, 1456]));
+        assert_eq!(closest_sum(&EXAMPLE, 1, 1000), Some(vec![979]));
+        assert_eq!(closest_sum(&EXAMPLE, 1, 100), None);
+        assert_eq!(closest_sum(&EXAMPLE, 0, 100), Some(vec![]));
+        assert_eq!(closest_sum(&EXAMPLE, 7, i64::MAX), None);
+
+        let inputs: [&[i64]; 4] = [&EXAMPLE, &[5, -3, 12, 0, 7, -9, 4], &[1, 1, 1, 1], &[]];
+        for numbers in inputs.iter() {
+            for k in 0..5 {
+                for target in -12..40 {
+                    let best = combinations(numbers.len(), k)
+                        .into_iter()
+                        .map(|c| c.iter().map(|&idx| numbers[idx]).sum::<i64>())
+                        .filter(|&sum| sum <= target)
+                        .max();
+                    let chosen = closest_sum(numbers, k, target);
+                    assert_eq!(chosen.as_ref().map(|c| c.iter().sum()), best);
+                    assert_eq!(chosen.map(|c| c.len()).unwrap_or(k), k);
                 }
             }
         }
     }
 
-    println!("3-sum not found");
+    #[test]
+    fn choose_test() {
+        assert_eq!(choose(&EXAMPLE, Mode::KSum(2), 2020), Some(vec![1721, 299]));
+        assert_eq!(choose(&EXAMPLE, Mode::SubsetSum, 2020), Some(vec![1721, 299]));
+        assert_eq!(choose(&EXAMPLE, Mode::ClosestSum(2), 2019), Some(vec![366, 1456]));
+    }
+
+    #[test]
+    fn entries_test() {
+        let report = "1721 979\n\n366\n";
+        let report = input(report).unwrap();
+        assert_eq!(report.numbers(), &[1721, 979, 366]);
+        assert_eq!(report.lines(), &[1, 1, 3]);
+
+        match entries("1721\n97x 366\n".as_bytes()).nth(1) {
+            Some(Err(Error::Day1 { location, .. })) => {
+                assert_eq!(location, Location { line: 2, column: 1 })
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+
+        // One long line read through a buffer smaller than it, with entries split across fills
+        let line = (0..1000).map(|n| n.to_string()).collect::<Vec<_>>().join(" \t");
+        let report = format!("{}\n\n{}0042 -0 é 12", line, "0".repeat(100));
+        let mut read = entries(BufReader::with_capacity(7, report.as_bytes()));
+        for n in 0..1000 {
+            assert_eq!(read.next().unwrap().unwrap(), Entry { value: n, line: 1 });
+        }
+        assert_eq!(read.next().unwrap().unwrap(), Entry { value: 42, line: 3 });
+        assert_eq!(read.next().unwrap().unwrap(), Entry { value: 0, line: 3 });
+        match read.next() {
+            Some(Err(Error::Day1 { location, .. })) => {
+                assert_eq!(location, Location { line: 3, column: 109 })
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+        assert_eq!(read.next().unwrap().unwrap(), Entry { value: 12, line: 3 });
+        assert!(read.next().is_none());
+
+        let too_long = format!("1 {} 2\n", "9".repeat(100));
+        let mut read = entries(BufReader::with_capacity(7, too_long.as_bytes()));
+        assert!(read.nth(1).unwrap().is_err());
+        assert_eq!(read.next().unwrap().unwrap(), Entry { value: 2, line: 1 });
+    }
+
+    #[test]
+    fn stream_two_sum_test() {
+        let stream = |report: &str| stream_two_sum(report.as_bytes(), TARGET);
+        let found = stream("1721\n979\n366\n299\n675\n1456\n").unwrap().unwrap();
+        assert_eq!(found.product, 514579);
+        assert_eq!(found.detail().as_deref(), Some("1721 on line 1, 299 on line 4"));
+
+        // The pair is found before the bad entry is read
+        assert_eq!(product(stream("1721\n299\nbad\n")), Some(514579));
+        assert!(stream("1721\nbad\n299\n").is_err());
+        assert_eq!(product(stream("1010\n")), None);
+    }
 
-    Ok(())
+    #[test]
+    fn stream_three_sum_test() {
+        let report = "1721\n979\n366\n299\n675\n1456\n";
+        for &max_in_memory in &[0, 1, 2, 4, 100] {
+            let found = stream_three_sum(report.as_bytes(), TARGET, max_in_memory).unwrap();
+            assert_eq!(
+                found.unwrap().detail().as_deref(),
+                Some("366 on line 3, 675 on line 5, 979 on line 2")
+            );
+        }
+
+        // Enough entries to spill several runs and span several blocks of the merged file
+        let mut state = 12345u64;
+        let numbers = (0..3000)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                (state >> 40) as i64 % 5000 - 2000
+            })
+            .collect::<Vec<_>>();
+        let report = numbers
+            .iter()
+            .map(|number| format!("{}\n", number))
+            .collect::<String>();
+        for &target in &[TARGET, -5999, 9000] {
+            let expected = k_sum(&numbers, 3, target).is_some();
+            let found = stream_three_sum(report.as_bytes(), target, 1000).unwrap();
+            assert_eq!(found.is_some(), expected);
+            if let Some(found) = found {
+                let values = found.entries.iter().map(|e| e.value).collect::<Vec<_>>();
+                assert_eq!(values.iter().sum::<i64>(), target);
+                assert!(values.windows(2).all(|pair| pair[0] <= pair[1]));
+                for entry in found.entries {
+                    assert_eq!(numbers[entry.line - 1], entry.value);
+                }
+            }
+        }
+
+        // More runs than are merged at once, so the merge takes several passes
+        let numbers = &numbers[..300];
+        let report = numbers
+            .iter()
+            .map(|number| format!("{}\n", number))
+            .collect::<String>();
+        assert!(numbers.len() / 2 > 2 * MERGE_FAN_IN);
+        for &target in &[TARGET, -5999, 9000] {
+            let found = stream_three_sum(report.as_bytes(), target, 2).unwrap();
+            assert_eq!(found.is_some(), k_sum(numbers, 3, target).is_some());
+            if let Some(found) = found {
+                assert_eq!(found.entries.iter().map(|e| e.value).sum::<i64>(), target);
+            }
+        }
+    }
 }
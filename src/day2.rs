@@ -1,3 +1,43 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    str::FromStr,
+};
+
+use nom::{
+    branch::alt,
+    bytes::complete::{is_not, tag},
+    character::complete::digit1,
+    combinator::{all_consuming, cut, map, map_res},
+    multi::fold_many0,
+    sequence::{delimited, preceded, tuple},
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{solution::Solution, Error, Location};
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum EntryError {
+    #[error("The range should start at 1 or more, positions are 1-based")]
+    ZeroBased,
+
+    #[error("The range {low}-{high} should not end before it starts")]
+    Reversed { low: usize, high: usize },
+}
+
+/// Checks a range read from an entry or a rule, which is 1-based and mustn't be reversed
+fn check_range(low: usize, high: usize) -> Result<(), EntryError> {
+    if low == 0 {
+        return Err(EntryError::ZeroBased);
+    }
+    if low > high {
+        return Err(EntryError::Reversed { low, high });
+    }
+    Ok(())
+}
+
+/// A line of the password database, e.g. `1-3 a: abcde`, borrowing its password from the input
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Entry<'a> {
     range_low: usize,
     range_high: usize,
@@ -5,8 +45,525 @@ pub struct Entry<'a> {
     string: &'a str,
 }
 
+impl<'a> Entry<'a> {
+    pub fn new(
+        range_low: usize,
+        range_high: usize,
+        letter: char,
+        password: &'a str,
+    ) -> Result<Self, EntryError> {
+        let entry = Entry {
+            range_low,
+            range_high,
+            letter,
+            string: password,
+        };
+        entry.validate()?;
+        Ok(entry)
+    }
+
+    fn validate(&self) -> Result<(), EntryError> {
+        check_range(self.range_low, self.range_high)
+    }
+
+    /// The two numbers before the letter, read as counts or positions depending on the policy
+    pub fn range(&self) -> (usize, usize) {
+        (self.range_low, self.range_high)
+    }
+
+    pub fn letter(&self) -> char {
+        self.letter
+    }
+
+    pub fn password(&self) -> &'a str {
+        self.string
+    }
+
+    /// How many times the letter appears in the password
+    pub fn letter_count(&self, semantics: Semantics) -> usize {
+        semantics.count(self.string, &[self.letter])
+    }
+
+    /// Whether the letter is at the 1-based `position` of the password
+    pub fn letter_at(&self, position: usize, semantics: Semantics) -> bool {
+        semantics
+            .unit_at(self.string, position)
+            .is_some_and(|unit| is_any(unit, &[self.letter]))
+    }
+
+    /// Checks the password as part 1 reads the rule, the letter appearing between
+    /// `range_low` and `range_high` times
+    pub fn count_verdict(&self, semantics: Semantics) -> Verdict<'a> {
+        let count = self.letter_count(semantics);
+        let violation = if count < self.range_low {
+            Some(Violation::TooFew)
+        } else if count > self.range_high {
+            Some(Violation::TooMany)
+        } else {
+            None
+        };
+        Verdict {
+            observed: Observed::Count(count),
+            violation,
+        }
+    }
+
+    /// Checks the password as part 2 reads the rule, the letter at exactly one of the
+    /// positions `range_low` and `range_high`
+    pub fn position_verdict(&self, semantics: Semantics) -> Verdict<'a> {
+        let low = semantics.unit_at(self.string, self.range_low);
+        let high = semantics.unit_at(self.string, self.range_high);
+        let is_letter = |unit: Option<&str>| unit.is_some_and(|unit| is_any(unit, &[self.letter]));
+        let violation = match (is_letter(low), is_letter(high)) {
+            (false, false) => Some(Violation::AtNeither),
+            (true, true) => Some(Violation::AtBoth),
+            _ => None,
+        };
+        Verdict {
+            observed: Observed::Positions { low, high },
+            violation,
+        }
+    }
+}
+
+impl fmt::Display for Entry<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{} {}: {}",
+            self.range_low, self.range_high, self.letter, self.string
+        )
+    }
+}
+
+/// An [`Entry`] that owns its password, so it can outlive the input it was parsed from
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OwnedEntry {
+    range_low: usize,
+    range_high: usize,
+    letter: char,
+    password: String,
+}
+
+impl OwnedEntry {
+    pub fn new(
+        range_low: usize,
+        range_high: usize,
+        letter: char,
+        password: impl Into<String>,
+    ) -> Result<Self, EntryError> {
+        let password = password.into();
+        Entry::new(range_low, range_high, letter, &password)?;
+        Ok(OwnedEntry {
+            range_low,
+            range_high,
+            letter,
+            password,
+        })
+    }
+
+    pub fn as_entry(&self) -> Entry<'_> {
+        Entry {
+            range_low: self.range_low,
+            range_high: self.range_high,
+            letter: self.letter,
+            string: &self.password,
+        }
+    }
+
+    pub fn range(&self) -> (usize, usize) {
+        (self.range_low, self.range_high)
+    }
+
+    pub fn letter(&self) -> char {
+        self.letter
+    }
+
+    pub fn password(&self) -> &str {
+        &self.password
+    }
+}
+
+impl From<Entry<'_>> for OwnedEntry {
+    fn from(entry: Entry<'_>) -> Self {
+        OwnedEntry {
+            range_low: entry.range_low,
+            range_high: entry.range_high,
+            letter: entry.letter,
+            password: entry.string.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for OwnedEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_entry().fmt(f)
+    }
+}
+
+/// Parses a single line, e.g. `1-3 a: abcde`
+impl FromStr for OwnedEntry {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_entry(s, s).map(OwnedEntry::from)
+    }
+}
+
+/// What checking an entry's password found
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Observed<'a> {
+    /// How many times the letter appears
+    Count(usize),
+    /// What's at positions `range_low` and `range_high`, None past the end
+    Positions {
+        low: Option<&'a str>,
+        high: Option<&'a str>,
+    },
+}
+
+/// Which part of an entry's rule its password broke
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// The letter appears fewer than `range_low` times
+    TooFew,
+    /// The letter appears more than `range_high` times
+    TooMany,
+    /// The letter is at neither position
+    AtNeither,
+    /// The letter is at both positions
+    AtBoth,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Verdict<'a> {
+    pub observed: Observed<'a>,
+    pub violation: Option<Violation>,
+}
+
+impl Verdict<'_> {
+    pub fn passed(&self) -> bool {
+        self.violation.is_none()
+    }
+}
+
+/// An entry whose password broke its rule, and on which line of the input
+pub struct Failure<'e, 'a> {
+    pub line: usize,
+    pub entry: &'e Entry<'a>,
+    pub verdict: Verdict<'a>,
+}
+
+impl fmt::Display for Failure<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Entry {
+            range_low,
+            range_high,
+            letter,
+            ..
+        } = self.entry;
+        write!(f, "line {}, `{}`: ", self.line, self.entry)?;
+
+        let describe = |unit: Option<&str>| {
+            let mut chars = unit.unwrap_or_default().chars();
+            match (unit, chars.next(), chars.next()) {
+                (None, _, _) => "nothing".to_string(),
+                (Some(_), Some(c), None) => format!("{:?}", c),
+                (Some(unit), _, _) => format!("{:?}", unit),
+            }
+        };
+        match (self.verdict.observed, self.verdict.violation) {
+            (Observed::Count(count), Some(Violation::TooFew)) => write!(
+                f,
+                "{:?} appears {} times, fewer than {}",
+                letter, count, range_low
+            ),
+            (Observed::Count(count), Some(Violation::TooMany)) => write!(
+                f,
+                "{:?} appears {} times, more than {}",
+                letter, count, range_high
+            ),
+            (Observed::Positions { low, high }, Some(violation)) => write!(
+                f,
+                "found {} at {} and {} at {}, {} {:?}",
+                describe(low),
+                range_low,
+                describe(high),
+                range_high,
+                if violation == Violation::AtBoth {
+                    "both are"
+                } else {
+                    "neither is"
+                },
+                letter
+            ),
+            _ => write!(f, "passed"),
+        }
+    }
+}
+
+/// Pairs entries read by [`input`], which are one to a line, with their line numbers
+pub fn numbered<'a>(input: &[Entry<'a>]) -> Vec<(usize, Entry<'a>)> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(idx, &entry)| (idx + 1, entry))
+        .collect()
+}
+
+/// Every entry whose password fails `check`, e.g. `Entry::count_verdict`, with its line
+pub fn failures<'e, 'a>(
+    input: &'e [(usize, Entry<'a>)],
+    check: impl Fn(&Entry<'a>) -> Verdict<'a>,
+) -> Vec<Failure<'e, 'a>> {
+    input
+        .iter()
+        .map(|(line, entry)| Failure {
+            line: *line,
+            entry,
+            verdict: check(entry),
+        })
+        .filter(|failure| !failure.verdict.passed())
+        .collect()
+}
+
+/// What the positions and counts in a rule are measured in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Semantics {
+    /// Positions are byte offsets, only matching a letter that starts at that byte
+    Bytes,
+    /// Positions count characters (Unicode scalar values)
+    #[default]
+    Chars,
+    /// Positions and counts are in grapheme clusters, so a letter followed by a combining
+    /// mark is no longer that letter
+    Graphemes,
+}
+
+impl Semantics {
+    /// What's at the 1-based `position` of `password`, None if it's past the end or, for
+    /// bytes, part way through a character
+    pub fn unit_at(self, password: &str, position: usize) -> Option<&str> {
+        let idx = position.checked_sub(1)?;
+        match self {
+            Semantics::Bytes => {
+                let rest = password.get(idx..)?;
+                rest.chars().next().map(|c| &rest[..c.len_utf8()])
+            }
+            Semantics::Chars => password
+                .char_indices()
+                .nth(idx)
+                .map(|(start, c)| &password[start..start + c.len_utf8()]),
+            Semantics::Graphemes => password.graphemes(true).nth(idx),
+        }
+    }
+
+    /// How many units of `password` are any of `letters`
+    pub fn count(self, password: &str, letters: &[char]) -> usize {
+        match self {
+            Semantics::Bytes | Semantics::Chars => {
+                password.chars().filter(|c| letters.contains(c)).count()
+            }
+            Semantics::Graphemes => password
+                .graphemes(true)
+                .filter(|unit| is_any(unit, letters))
+                .count(),
+        }
+    }
+}
+
+/// Whether `unit` is exactly one of `letters`, and nothing more
+fn is_any(unit: &str, letters: &[char]) -> bool {
+    let mut chars = unit.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => letters.contains(&c),
+        _ => false,
+    }
+}
+
+/// A reading of an entry's range and letter as a rule its password has to follow
+pub trait PasswordPolicy {
+    fn is_valid(&self, entry: &Entry<'_>) -> bool;
+}
+
+impl<F> PasswordPolicy for F
+where
+    F: Fn(&Entry<'_>) -> bool,
+{
+    fn is_valid(&self, entry: &Entry<'_>) -> bool {
+        self(entry)
+    }
+}
+
+/// The letter appears between `range_low` and `range_high` times, inclusive (part 1)
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CountInRange {
+    pub semantics: Semantics,
+}
+
+impl PasswordPolicy for CountInRange {
+    fn is_valid(&self, entry: &Entry<'_>) -> bool {
+        entry.count_verdict(self.semantics).passed()
+    }
+}
+
+/// The letter is at exactly one of the 1-based positions `range_low` and `range_high` (part 2)
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ExactlyOnePosition {
+    pub semantics: Semantics,
+}
+
+impl PasswordPolicy for ExactlyOnePosition {
+    fn is_valid(&self, entry: &Entry<'_>) -> bool {
+        entry.position_verdict(self.semantics).passed()
+    }
+}
+
+/// The letter is at neither of the 1-based positions `range_low` and `range_high`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LetterNotAtPositions {
+    pub semantics: Semantics,
+}
+
+impl PasswordPolicy for LetterNotAtPositions {
+    fn is_valid(&self, entry: &Entry<'_>) -> bool {
+        !entry.letter_at(entry.range_low, self.semantics)
+            && !entry.letter_at(entry.range_high, self.semantics)
+    }
+}
+
+/// The password has at least this many distinct letters, whatever the entry's rule says
+pub struct DistinctLetters(pub usize);
+
+impl PasswordPolicy for DistinctLetters {
+    fn is_valid(&self, entry: &Entry<'_>) -> bool {
+        let letters = entry
+            .string
+            .chars()
+            .filter(|c| c.is_alphabetic())
+            .collect::<HashSet<_>>();
+        letters.len() >= self.0
+    }
+}
+
+/// Password policies by name, starting with the built-in ones
+pub struct Policies {
+    policies: HashMap<String, Box<dyn PasswordPolicy>>,
+}
+
+impl Policies {
+    /// Adds `policy` under `name`, returning the policy it replaces, if any
+    pub fn register(
+        &mut self,
+        name: &str,
+        policy: impl PasswordPolicy + 'static,
+    ) -> Option<Box<dyn PasswordPolicy>> {
+        self.policies.insert(name.to_string(), Box::new(policy))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn PasswordPolicy> {
+        self.policies.get(name).map(|policy| &**policy)
+    }
+
+    /// The registered names, sorted
+    pub fn names(&self) -> Vec<&str> {
+        let mut names = self.policies.keys().map(String::as_str).collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+}
+
+impl Default for Policies {
+    fn default() -> Self {
+        let mut policies = Policies {
+            policies: HashMap::new(),
+        };
+        policies.register("count-in-range", CountInRange::default());
+        policies.register("exactly-one-position", ExactlyOnePosition::default());
+        policies.register("letter-not-at-positions", LetterNotAtPositions::default());
+        policies
+    }
+}
+
+/// How many entries' passwords follow `policy`
+pub fn count_valid(input: &[Entry<'_>], policy: &(impl PasswordPolicy + ?Sized)) -> usize {
+    input.iter().filter(|entry| policy.is_valid(entry)).count()
+}
+
+/// A compound rule, e.g. `1-3 a AND 2-9 c` or `NOT position 4 is x`.
+///
+/// Clauses are either a count range or a position, each over one or more letters. NOT binds
+/// tightest, then AND, then OR, and parentheses group.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Rule {
+    /// Between `low` and `high` of the password's characters, inclusive, are any of `letters`
+    Count {
+        low: usize,
+        high: usize,
+        letters: Vec<char>,
+    },
+    /// The character at the 1-based `position` is any of `letters`
+    Position { position: usize, letters: Vec<char> },
+    Not(Box<Rule>),
+    And(Box<Rule>, Box<Rule>),
+    Or(Box<Rule>, Box<Rule>),
+}
+
+impl Rule {
+    pub fn is_satisfied_by(&self, password: &str, semantics: Semantics) -> bool {
+        match self {
+            Rule::Count { low, high, letters } => {
+                let count = semantics.count(password, letters);
+                *low <= count && count <= *high
+            }
+            Rule::Position { position, letters } => semantics
+                .unit_at(password, *position)
+                .is_some_and(|unit| is_any(unit, letters)),
+            Rule::Not(rule) => !rule.is_satisfied_by(password, semantics),
+            Rule::And(left, right) => {
+                left.is_satisfied_by(password, semantics)
+                    && right.is_satisfied_by(password, semantics)
+            }
+            Rule::Or(left, right) => {
+                left.is_satisfied_by(password, semantics)
+                    || right.is_satisfied_by(password, semantics)
+            }
+        }
+    }
+}
+
+/// Checks an entry's password against this rule in characters, ignoring the entry's own
+impl PasswordPolicy for Rule {
+    fn is_valid(&self, entry: &Entry<'_>) -> bool {
+        self.is_satisfied_by(entry.string, Semantics::default())
+    }
+}
+
+impl FromStr for Rule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        all_consuming(take_rule)(s)
+            .map(|(_, rule)| rule)
+            .map_err(|e| parse_error(s, s, e, "a rule like `1-3 a AND NOT position 4 is x`"))
+    }
+}
+
+/// A password audited against its own compound rule, from a line like `1-3 a OR 2-9 c: abcde`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Audit<'a> {
+    pub rule: Rule,
+    pub password: &'a str,
+}
+
+impl<'a> Audit<'a> {
+    pub fn is_valid(&self, semantics: Semantics) -> bool {
+        self.rule.is_satisfied_by(self.password, semantics)
+    }
+}
+
 fn read_base_10_number(input: &str) -> Result<usize, std::num::ParseIntError> {
-    usize::from_str_radix(input, 10)
+    input.parse::<usize>()
 }
 
 fn take_hypenated_range(input: &str) -> nom::IResult<&str, (usize, usize)> {
@@ -18,7 +575,93 @@ fn take_hypenated_range(input: &str) -> nom::IResult<&str, (usize, usize)> {
     Ok((rest, (range_low, range_high)))
 }
 
-fn take_entry(input: &str) -> nom::IResult<&str, Entry> {
+/// Fails outright at `input` if `check` does, as no other clause could read it instead
+fn checked<'a, T>(
+    input: &'a str,
+    (rest, value): (&'a str, T),
+    check: Result<(), EntryError>,
+) -> nom::IResult<&'a str, T> {
+    match check {
+        Ok(()) => Ok((rest, value)),
+        Err(_) => Err(nom::Err::Failure(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Verify,
+        ))),
+    }
+}
+
+/// A range in a rule, checked like an entry's
+fn take_clause_range(input: &str) -> nom::IResult<&str, (usize, usize)> {
+    let (rest, (low, high)) = take_hypenated_range(input)?;
+    checked(input, (rest, (low, high)), check_range(low, high))
+}
+
+fn take_position(input: &str) -> nom::IResult<&str, usize> {
+    let (rest, position) = map_res(digit1, read_base_10_number)(input)?;
+    checked(input, (rest, position), check_range(position, position))
+}
+
+fn take_letters(input: &str) -> nom::IResult<&str, Vec<char>> {
+    map(is_not(" :()"), |letters: &str| letters.chars().collect())(input)
+}
+
+fn take_clause(input: &str) -> nom::IResult<&str, Rule> {
+    alt((
+        map(
+            tuple((take_clause_range, tag(" "), take_letters)),
+            |((low, high), _, letters)| Rule::Count { low, high, letters },
+        ),
+        map(
+            tuple((
+                tag("position "),
+                take_position,
+                tag(" is "),
+                take_letters,
+            )),
+            |(_, position, _, letters)| Rule::Position { position, letters },
+        ),
+    ))(input)
+}
+
+// Past an operator or an opening bracket only an operand can follow, so these `cut` to point at
+// the operand rather than backtracking to the operator
+fn take_negation(input: &str) -> nom::IResult<&str, Rule> {
+    alt((
+        map(preceded(tag("NOT "), cut(take_negation)), |rule| {
+            Rule::Not(Box::new(rule))
+        }),
+        delimited(tag("("), cut(take_rule), cut(tag(")"))),
+        take_clause,
+    ))(input)
+}
+
+fn take_conjunction(input: &str) -> nom::IResult<&str, Rule> {
+    let (rest, first) = take_negation(input)?;
+    fold_many0(preceded(tag(" AND "), cut(take_negation)), first, |left, right| {
+        Rule::And(Box::new(left), Box::new(right))
+    })(rest)
+}
+
+fn take_rule(input: &str) -> nom::IResult<&str, Rule> {
+    let (rest, first) = take_conjunction(input)?;
+    fold_many0(preceded(tag(" OR "), cut(take_conjunction)), first, |left, right| {
+        Rule::Or(Box::new(left), Box::new(right))
+    })(rest)
+}
+
+fn take_audit(input: &str) -> nom::IResult<&str, Audit<'_>> {
+    let (rest, rule) = take_rule(input)?;
+    let (rest, _) = tag(": ")(rest)?;
+    Ok((
+        rest,
+        Audit {
+            rule,
+            password: rest,
+        },
+    ))
+}
+
+fn take_entry(input: &str) -> nom::IResult<&str, Entry<'_>> {
     let (rest, (range_low, range_high)) = take_hypenated_range(input)?;
     let (rest, _) = nom::bytes::complete::tag(" ")(rest)?;
 
@@ -37,42 +680,434 @@ fn take_entry(input: &str) -> nom::IResult<&str, Entry> {
     ))
 }
 
-pub fn input<'a>(input: &'a str) -> Result<Vec<Entry<'a>>, anyhow::Error> {
+/// Turns a nom error from parsing `part` of `input` into one pointing at where it failed
+fn parse_error(
+    input: &str,
+    part: &str,
+    e: nom::Err<nom::error::Error<&str>>,
+    expected: &str,
+) -> Error {
+    let (rest, kind) = match e {
+        nom::Err::Error(e) | nom::Err::Failure(e) => (e.input, e.code),
+        nom::Err::Incomplete(_) => (&part[part.len()..], nom::error::ErrorKind::Eof),
+    };
+    Error::Day2 {
+        location: Location::of(input, rest),
+        message: format!("expected {}, {} failed", expected, kind.description()),
+    }
+}
+
+/// Parses `line` of `input` as an entry and checks its range
+fn parse_entry<'a>(input: &str, line: &'a str) -> Result<Entry<'a>, Error> {
+    let (_, entry) =
+        take_entry(line).map_err(|e| parse_error(input, line, e, "an entry like `1-3 a: abcde`"))?;
+    entry.validate().map_err(|e| Error::Day2 {
+        location: Location::of(input, line),
+        message: e.to_string(),
+    })?;
+    Ok(entry)
+}
+
+pub fn input(input: &str) -> Result<Vec<Entry<'_>>, Error> {
     input
         .lines()
-        .map(|line| -> Result<Entry, anyhow::Error> {
-            take_entry(line)
-                .map(|(_, e)| e)
-                .map_err(|e| anyhow::anyhow!("Entry parsing error: {}", e.to_string()))
-        })
+        .map(|line| parse_entry(input, line))
         .collect::<Result<Vec<_>, _>>()
 }
 
-pub fn part1<'a>(input: &[Entry<'a>]) {
-    let count = input
-        .iter()
-        .filter(|entry| {
-            let matching_count = entry.string.chars().filter(|c| *c == entry.letter).count();
-            entry.range_low <= matching_count && matching_count <= entry.range_high
-        })
-        .count();
-    println!("Count of valid passwords: {}", count);
+/// How [`parse`] treats lines that aren't clean entries
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Fails on the first bad line, also rejecting whitespace in the letter or password
+    Strict,
+    /// Skips lines `input` would fail on, returning their errors as warnings
+    Lenient,
 }
 
-pub fn part2<'a>(input: &[Entry<'a>]) {
-    let count = input
-        .iter()
-        .filter(|entry| {
-            entry
-                .string
-                .char_indices()
-                .filter(|(idx, c)| {
-                    (idx + 1 == entry.range_low || idx + 1 == entry.range_high)
-                        && *c == entry.letter
-                })
-                .count()
-                == 1
+/// Entries parsed in a [`ParseMode`] with the lines they're on, along with the errors for any
+/// lines skipped
+#[derive(Debug)]
+pub struct Parsed<'a> {
+    pub entries: Vec<(usize, Entry<'a>)>,
+    pub warnings: Vec<Error>,
+}
+
+impl<'a> Parsed<'a> {
+    /// The entries without their lines, to pass to e.g. [`part1`]
+    pub fn without_lines(&self) -> Vec<Entry<'a>> {
+        self.entries.iter().map(|&(_, entry)| entry).collect()
+    }
+}
+
+/// Points at the first whitespace in an entry's letter or password
+fn check_no_whitespace(input: &str, entry: &Entry<'_>) -> Result<(), Error> {
+    if entry.letter.is_whitespace() {
+        // The letter comes just before the ": " ahead of the password
+        let password_start = entry.string.as_ptr() as usize - input.as_ptr() as usize;
+        let letter_start = password_start - ": ".len() - entry.letter.len_utf8();
+        return Err(Error::Day2 {
+            location: Location::of(input, &input[letter_start..]),
+            message: format!("the letter should not be whitespace, got {:?}", entry.letter),
+        });
+    }
+
+    match entry.string.char_indices().find(|(_, c)| c.is_whitespace()) {
+        Some((idx, c)) => Err(Error::Day2 {
+            location: Location::of(input, &entry.string[idx..]),
+            message: format!("the password should not contain whitespace, got {:?}", c),
+        }),
+        None => Ok(()),
+    }
+}
+
+pub fn parse(input: &str, mode: ParseMode) -> Result<Parsed<'_>, Error> {
+    let mut parsed = Parsed {
+        entries: Vec::new(),
+        warnings: Vec::new(),
+    };
+    for (idx, line) in input.lines().enumerate() {
+        match (parse_entry(input, line), mode) {
+            (Ok(entry), ParseMode::Strict) => {
+                check_no_whitespace(input, &entry)?;
+                parsed.entries.push((idx + 1, entry));
+            }
+            (Ok(entry), ParseMode::Lenient) => parsed.entries.push((idx + 1, entry)),
+            (Err(e), ParseMode::Strict) => return Err(e),
+            (Err(e), ParseMode::Lenient) => parsed.warnings.push(e),
+        }
+    }
+    Ok(parsed)
+}
+
+/// Parses lines each giving a compound rule and the password to check against it
+pub fn audits(input: &str) -> Result<Vec<Audit<'_>>, Error> {
+    input
+        .lines()
+        .map(|line| {
+            take_audit(line).map(|(_, audit)| audit).map_err(|e| {
+                parse_error(input, line, e, "an audit like `1-3 a AND 2-9 c: abcde`")
+            })
         })
-        .count();
-    println!("Count of valid passwords: {}", count);
+        .collect::<Result<Vec<_>, _>>()
+}
+
+pub fn part1(input: &[Entry<'_>]) -> usize {
+    count_valid(input, &CountInRange::default())
+}
+
+pub fn part2(input: &[Entry<'_>]) -> usize {
+    count_valid(input, &ExactlyOnePosition::default())
+}
+
+pub struct Day2;
+
+impl<'a> Solution<'a> for Day2 {
+    type Input = Vec<Entry<'a>>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(raw: &'a str) -> Result<Self::Input, Error> {
+        input(raw)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        Ok(part1(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Ok(part2(input))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n";
+
+    #[test]
+    fn example_test() {
+        let input = input(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 2);
+        assert_eq!(part2(&input), 1);
+    }
+
+    #[test]
+    fn verdict_test() {
+        let input = input(EXAMPLE).unwrap();
+        assert_eq!(
+            input[2].count_verdict(Semantics::Chars),
+            Verdict {
+                observed: Observed::Count(9),
+                violation: None
+            }
+        );
+        assert_eq!(
+            input[0].position_verdict(Semantics::Chars),
+            Verdict {
+                observed: Observed::Positions {
+                    low: Some("a"),
+                    high: Some("c")
+                },
+                violation: None
+            }
+        );
+
+        let report = |failures: Vec<Failure<'_, '_>>| {
+            failures
+                .iter()
+                .map(Failure::to_string)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            report(failures(&numbered(&input), |e| e.count_verdict(Semantics::Chars))),
+            vec!["line 2, `1-3 b: cdefg`: 'b' appears 0 times, fewer than 1"]
+        );
+        assert_eq!(
+            report(failures(&numbered(&input), |e| e.position_verdict(Semantics::Chars))),
+            vec![
+                "line 2, `1-3 b: cdefg`: found 'c' at 1 and 'e' at 3, neither is 'b'",
+                "line 3, `2-9 c: ccccccccc`: found 'c' at 2 and 'c' at 9, both are 'c'"
+            ]
+        );
+
+        let short = super::input("1-9 a: abc\n2-2 a: aaa\n").unwrap();
+        assert_eq!(
+            short[0].position_verdict(Semantics::Chars).observed,
+            Observed::Positions {
+                low: Some("a"),
+                high: None
+            }
+        );
+        assert_eq!(
+            report(failures(&numbered(&short), |e| e.count_verdict(Semantics::Chars))),
+            vec!["line 2, `2-2 a: aaa`: 'a' appears 3 times, more than 2"]
+        );
+    }
+
+    #[test]
+    fn unicode_test() {
+        // é is two bytes, so byte positions drift from character positions after it
+        let input = input("1-3 é: éaé\n1-2 ß: ßßx\n2-4 ü: aüüb\n").unwrap();
+        let valid = |policy: &dyn PasswordPolicy| {
+            input
+                .iter()
+                .map(|entry| policy.is_valid(entry))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(part1(&input), 3);
+        assert_eq!(valid(&ExactlyOnePosition::default()), vec![false, false, true]);
+        assert_eq!(part2(&input), 1);
+        assert_eq!(
+            valid(&ExactlyOnePosition {
+                semantics: Semantics::Bytes
+            }),
+            vec![true, true, false]
+        );
+        assert_eq!(Semantics::Bytes.unit_at("ßx", 2), None);
+        assert_eq!(Semantics::Chars.unit_at("ßx", 2), Some("x"));
+        assert_eq!(Semantics::Chars.unit_at("ßx", 0), None);
+
+        // e followed by a combining acute accent is one grapheme, but not the letter e
+        let combining = super::input("1-2 e: e\u{301}x\n").unwrap();
+        assert_eq!(combining[0].letter_count(Semantics::Chars), 1);
+        assert_eq!(combining[0].letter_count(Semantics::Graphemes), 0);
+        assert!(combining[0].position_verdict(Semantics::Chars).passed());
+        assert_eq!(
+            combining[0].position_verdict(Semantics::Graphemes),
+            Verdict {
+                observed: Observed::Positions {
+                    low: Some("e\u{301}"),
+                    high: Some("x")
+                },
+                violation: Some(Violation::AtNeither)
+            }
+        );
+        let combining = numbered(&combining);
+        assert_eq!(
+            failures(&combining, |e| e.position_verdict(Semantics::Graphemes))[0].to_string(),
+            "line 1, `1-2 e: e\u{301}x`: found \"e\\u{301}\" at 1 and 'x' at 2, neither is 'e'"
+        );
+
+        // Byte 3 is part way through the first ü
+        let rule = "position 3 is ü AND 2-2 ü".parse::<Rule>().unwrap();
+        assert!(rule.is_satisfied_by("aüüb", Semantics::Chars));
+        assert!(!rule.is_satisfied_by("aüüb", Semantics::Bytes));
+    }
+
+    #[test]
+    fn audits_test() {
+        let audits = audits(
+            "1-3 a AND 2-9 c: abcde\n\
+             NOT position 4 is x: abcxe\n\
+             1-3 ab OR position 2 is xyz: bbbb\n\
+             NOT (1-3 a OR 1-1 b) AND position 1 is c: cdefg\n\
+             1-3 a: abcde\n",
+        )
+        .unwrap();
+        assert_eq!(
+            audits
+                .iter()
+                .map(|audit| audit.is_valid(Semantics::Chars))
+                .collect::<Vec<_>>(),
+            vec![false, false, false, true, true]
+        );
+
+        assert_eq!(
+            audits[1].rule,
+            Rule::Not(Box::new(Rule::Position {
+                position: 4,
+                letters: vec!['x']
+            }))
+        );
+        // AND binds tighter than OR
+        assert_eq!(
+            "1-1 a OR 1-1 b AND 1-1 c".parse::<Rule>().unwrap(),
+            Rule::Or(
+                Box::new("1-1 a".parse().unwrap()),
+                Box::new(Rule::And(
+                    Box::new("1-1 b".parse().unwrap()),
+                    Box::new("1-1 c".parse().unwrap())
+                ))
+            )
+        );
+
+        let error = super::audits("1-3 a AND\n").unwrap_err();
+        assert_eq!(error.location(), Some(Location { line: 1, column: 6 }));
+        let error = super::audits("1-3 a: abc\n1-3 a AND zz: x\n").unwrap_err();
+        assert_eq!(error.location(), Some(Location { line: 2, column: 11 }));
+        let error = super::audits("1-3 a AND 1-1 b OR (x: abc\n").unwrap_err();
+        assert_eq!(error.location(), Some(Location { line: 1, column: 21 }));
+        let error = super::audits("NOT (1-1 a: abc\n").unwrap_err();
+        assert_eq!(error.location(), Some(Location { line: 1, column: 11 }));
+        assert!("1-3 a AND".parse::<Rule>().is_err());
+
+        // Clauses are held to the same ranges as entries, pointing at the clause at fault
+        let column = |input: &str| super::audits(input).unwrap_err().location().unwrap();
+        assert_eq!(column("3-1 a: abc\n"), Location { line: 1, column: 1 });
+        assert_eq!(column("0-1 a: abc\n"), Location { line: 1, column: 1 });
+        assert_eq!(
+            column("1-3 a: abc\n1-3 a AND position 0 is a: abc\n"),
+            Location { line: 2, column: 20 }
+        );
+        assert_eq!(
+            column("1-3 a: abc\nNOT (1-1 a OR 2-1 b): abc\n"),
+            Location { line: 2, column: 15 }
+        );
+        assert!("position 0 is a".parse::<Rule>().is_err());
+        assert!("3-1 a".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn entry_test() {
+        let owned = "1-3 a: abcde".parse::<OwnedEntry>().unwrap();
+        assert_eq!(owned.to_string(), "1-3 a: abcde");
+        assert_eq!((owned.range(), owned.letter(), owned.password()), ((1, 3), 'a', "abcde"));
+        assert_eq!(
+            owned,
+            OwnedEntry::new(1, 3, 'a', "abcde".to_string()).unwrap()
+        );
+
+        let input = input(EXAMPLE).unwrap();
+        let lines = input.iter().map(Entry::to_string).collect::<Vec<_>>();
+        assert_eq!(lines.join("\n") + "\n", EXAMPLE);
+        assert_eq!(OwnedEntry::from(input[1]).as_entry(), input[1]);
+        assert_eq!(Entry::new(2, 9, 'c', "ccccccccc"), Ok(input[2]));
+
+        assert_eq!(Entry::new(0, 3, 'a', "abc"), Err(EntryError::ZeroBased));
+        assert_eq!(
+            OwnedEntry::new(3, 1, 'a', "abc"),
+            Err(EntryError::Reversed { low: 3, high: 1 })
+        );
+        let error = super::input("1-3 a: abcde\n3-1 a: abc\n").unwrap_err();
+        assert_eq!(error.location(), Some(Location { line: 2, column: 1 }));
+        assert!("0-1 a: abc".parse::<OwnedEntry>().is_err());
+        assert!("1-3 a abc".parse::<OwnedEntry>().is_err());
+    }
+
+    #[test]
+    fn parse_mode_test() {
+        let strict = parse(EXAMPLE, ParseMode::Strict).unwrap();
+        assert_eq!(strict.without_lines(), input(EXAMPLE).unwrap());
+        assert_eq!(strict.entries, numbered(&input(EXAMPLE).unwrap()));
+        assert!(strict.warnings.is_empty());
+
+        let column = |input: &str| {
+            parse(input, ParseMode::Strict)
+                .unwrap_err()
+                .location()
+                .unwrap()
+        };
+        assert_eq!(column("1-3 a: abcde\n1-3 b: cd efg\n"), Location { line: 2, column: 10 });
+        assert_eq!(column("1-3 a: abcde\t\n"), Location { line: 1, column: 13 });
+        assert_eq!(column("1-3  : abcde\n"), Location { line: 1, column: 5 });
+        assert_eq!(column("1-3 a abcde\n"), Location { line: 1, column: 6 });
+        // The default parse takes everything after the colon as the password
+        assert_eq!(input("1-3 b: cd efg\n").unwrap()[0].password(), "cd efg");
+
+        let lenient = parse(
+            "1-3 a: abcde\nnot an entry\n1-3 b: cdefg\n3-1 c: ccc\n2-9 c: ccccccccc\n",
+            ParseMode::Lenient,
+        )
+        .unwrap();
+        assert_eq!(part1(&lenient.without_lines()), 2);
+        assert_eq!(part2(&lenient.without_lines()), 1);
+        assert_eq!(
+            lenient
+                .warnings
+                .iter()
+                .map(|warning| warning.location().unwrap().line)
+                .collect::<Vec<_>>(),
+            vec![2, 4]
+        );
+
+        // Failures keep the lines the entries came from, past the skipped ones
+        let lines = |failures: Vec<Failure<'_, '_>>| {
+            failures
+                .iter()
+                .map(Failure::to_string)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            lines(failures(&lenient.entries, |e| e.count_verdict(Semantics::Chars))),
+            vec!["line 3, `1-3 b: cdefg`: 'b' appears 0 times, fewer than 1"]
+        );
+        assert_eq!(
+            lines(failures(&lenient.entries, |e| e.position_verdict(Semantics::Chars))),
+            vec![
+                "line 3, `1-3 b: cdefg`: found 'c' at 1 and 'e' at 3, neither is 'b'",
+                "line 5, `2-9 c: ccccccccc`: found 'c' at 2 and 'c' at 9, both are 'c'"
+            ]
+        );
+    }
+
+    #[test]
+    fn policies_test() {
+        let input = input(EXAMPLE).unwrap();
+        let mut policies = Policies::default();
+        assert_eq!(
+            policies.names(),
+            vec![
+                "count-in-range",
+                "exactly-one-position",
+                "letter-not-at-positions"
+            ]
+        );
+        assert_eq!(count_valid(&input, policies.get("count-in-range").unwrap()), 2);
+        assert_eq!(
+            count_valid(&input, policies.get("letter-not-at-positions").unwrap()),
+            1
+        );
+
+        assert!(policies.register("distinct", DistinctLetters(5)).is_none());
+        assert_eq!(count_valid(&input, policies.get("distinct").unwrap()), 2);
+
+        let ends_with_letter = |entry: &Entry<'_>| entry.password().ends_with(entry.letter());
+        assert!(policies.register("distinct", ends_with_letter).is_some());
+        assert_eq!(count_valid(&input, policies.get("distinct").unwrap()), 1);
+        assert!(policies.get("missing").is_none());
+
+        policies.register("no-e-at-5", "NOT position 5 is e".parse::<Rule>().unwrap());
+        assert_eq!(count_valid(&input, policies.get("no-e-at-5").unwrap()), 2);
+    }
 }
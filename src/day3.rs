@@ -1,3 +1,11 @@
+use std::convert::TryFrom;
+
+use crate::{
+    grid::{Grid, GridError, Wrap},
+    solution::Solution,
+    Error, Location,
+};
+
 #[derive(thiserror::Error, Debug)]
 pub enum TreeChartError {
     #[error("The width should be set to something other than 0")]
@@ -5,6 +13,75 @@ pub enum TreeChartError {
 
     #[error("Number of elements indivisible by width")]
     DimensionError,
+
+    #[error("Row {row} is {actual} wide, but the first row is {expected}")]
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        actual: usize,
+    },
+
+    #[error("Unexpected character {character:?} at row {row}, column {column}")]
+    UnexpectedCharacter {
+        row: usize,
+        column: usize,
+        character: char,
+    },
+
+    #[error("The slope should move at least one row per step, or it never leaves the chart")]
+    ZeroRowStep,
+}
+
+impl TreeChartError {
+    /// Where in the input the error was found, for errors from reading a particular row
+    pub fn location(&self) -> Option<Location> {
+        match *self {
+            TreeChartError::RaggedRow {
+                row,
+                expected,
+                actual,
+            } => Some(Location {
+                line: row,
+                column: expected.min(actual) + 1,
+            }),
+            TreeChartError::UnexpectedCharacter { row, column, .. } => Some(Location {
+                line: row,
+                column,
+            }),
+            TreeChartError::WidthUnset
+            | TreeChartError::DimensionError
+            | TreeChartError::ZeroRowStep => None,
+        }
+    }
+}
+
+/// The characters marking open and tree cells in a chart
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Alphabet {
+    pub open: char,
+    pub tree: char,
+}
+
+impl Alphabet {
+    /// Whether `character` is a tree, or None if it's neither open nor a tree
+    pub fn tree_present(&self, character: char) -> Option<bool> {
+        if character == self.tree {
+            Some(true)
+        } else if character == self.open {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for Alphabet {
+    fn default() -> Self {
+        Alphabet {
+            open: '.',
+            tree: '#',
+        }
+    }
 }
 
 pub struct TreeChartBuilder {
@@ -29,49 +106,82 @@ impl TreeChartBuilder {
     }
 
     pub fn build(self) -> Result<TreeChart, TreeChartError> {
-        if self.width == 0 {
-            return Err(TreeChartError::WidthUnset);
-        }
-
-        if self.chart.len() % self.width != 0 {
-            return Err(TreeChartError::DimensionError);
-        }
+        let grid = Grid::new(self.chart, self.width).map_err(|e| match e {
+            GridError::ZeroWidth => TreeChartError::WidthUnset,
+            GridError::Indivisible { .. } => TreeChartError::DimensionError,
+        })?;
 
         Ok(TreeChart {
-            chart: self.chart,
-            width: self.width,
+            grid: grid.with_wrap(Wrap::Horizontal),
         })
     }
 }
 
+impl Default for TreeChartBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where the trees are, repeating to the right forever
 pub struct TreeChart {
-    chart: Vec<bool>,
-    width: usize,
+    grid: Grid<bool>,
 }
 
 impl TreeChart {
+    /// Whether each cell has a tree, wrapping horizontally
+    pub fn grid(&self) -> &Grid<bool> {
+        &self.grid
+    }
+
     pub fn get(&self, (x, y): (usize, usize)) -> Option<(bool, (usize, usize))> {
         //     ➡
         //     y
         // ⬇ x
-        let y = y % self.width;
+        let y = y % self.grid.width();
 
-        let tree_present = self.chart.get(x * self.width + y).cloned();
+        let tree_present = self.grid.get((x, y)).cloned();
         tree_present.map(|t| (t, (x, y)))
     }
 
-    pub fn iter(&self, offset: (usize, usize)) -> TreeChartIterator {
-        TreeChartIterator {
-            offset,
-            position: (0, 0),
-            chart: self,
+    /// Walks the chart `offset` at a time, rows down and columns right with negative steps going
+    /// up and left. Starts from the top left, or the bottom left when going up.
+    pub fn iter(&self, offset: (isize, isize)) -> Result<TreeChartIterator<'_>, TreeChartError> {
+        let start_x = if offset.0 < 0 {
+            self.grid.height().saturating_sub(1)
+        } else {
+            0
+        };
+        self.iter_from((start_x, 0), offset)
+    }
+
+    /// Like `iter`, but starting from `start` until the walk leaves the top or bottom
+    pub fn iter_from(
+        &self,
+        (x, y): (usize, usize),
+        offset: (isize, isize),
+    ) -> Result<TreeChartIterator<'_>, TreeChartError> {
+        if offset.0 == 0 {
+            return Err(TreeChartError::ZeroRowStep);
         }
+
+        // Columns wrap, so a column step only matters up to a multiple of the width
+        let width = self.grid.width();
+        Ok(TreeChartIterator {
+            row_step: offset.0,
+            column_step: offset.1.rem_euclid(width as isize) as usize,
+            position: isize::try_from(x).ok().map(|x| (x, y % width)),
+            chart: self,
+        })
     }
 }
 
 pub struct TreeChartIterator<'a> {
-    offset: (usize, usize),
-    position: (usize, usize),
+    row_step: isize,
+    /// Always less than the chart's width
+    column_step: usize,
+    /// None once the row is past what an `isize` can hold
+    position: Option<(isize, usize)>,
     chart: &'a TreeChart,
 }
 
@@ -79,47 +189,197 @@ impl<'a> Iterator for TreeChartIterator<'a> {
     type Item = bool;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some((tree_present, (x, wrapped_y))) = self.chart.get(self.position) {
-            self.position = (x + self.offset.0, wrapped_y + self.offset.1);
-            Some(tree_present)
-        } else {
-            None
-        }
+        let (x, y) = self.position?;
+        let (x, y) = self.chart.grid.resolve((x, y as isize))?;
+        self.position = (x as isize)
+            .checked_add(self.row_step)
+            .map(|next_x| (next_x, (y + self.column_step) % self.chart.grid.width()));
+        Some(self.chart.grid[(x, y)])
     }
 }
 
 pub fn input(input: &str) -> Result<TreeChart, TreeChartError> {
+    input_with_alphabet(input, Alphabet::default())
+}
+
+/// Reads a chart marked out in `alphabet`, every row as wide as the first
+pub fn input_with_alphabet(input: &str, alphabet: Alphabet) -> Result<TreeChart, TreeChartError> {
     let mut builder = TreeChartBuilder::new();
+    let mut expected_width = None;
     for (idx, line) in input.lines().enumerate() {
+        let row = idx + 1;
         let mut width = 0usize;
-        for letter in line.chars() {
-            builder.append(letter == '#');
+        for (column_idx, character) in line.chars().enumerate() {
+            let tree_present = alphabet.tree_present(character).ok_or(
+                TreeChartError::UnexpectedCharacter {
+                    row,
+                    column: column_idx + 1,
+                    character,
+                },
+            )?;
+            builder.append(tree_present);
             width += 1;
         }
-        if idx == 0 {
-            builder.set_width(width);
+
+        match expected_width {
+            None => {
+                builder.set_width(width);
+                expected_width = Some(width);
+            }
+            Some(expected) if expected != width => {
+                return Err(TreeChartError::RaggedRow {
+                    row,
+                    expected,
+                    actual: width,
+                })
+            }
+            Some(_) => {}
         }
     }
 
     builder.build()
 }
 
-pub fn count_trees_encountered(chart: &TreeChart, offset: (usize, usize)) -> usize {
-    chart
-        .iter(offset)
+pub fn count_trees_encountered(
+    chart: &TreeChart,
+    offset: (isize, isize),
+) -> Result<usize, TreeChartError> {
+    Ok(chart
+        .iter(offset)?
         .map(|tree_present| if tree_present { 1 } else { 0 })
-        .sum()
+        .sum())
 }
 
-pub fn part1(input: &TreeChart) {
-    let tree_amt: usize = count_trees_encountered(input, (1, 3));
-    println!("Trees encountered: {}", tree_amt);
+pub fn part1(input: &TreeChart) -> Result<usize, TreeChartError> {
+    count_trees_encountered(input, (1, 3))
 }
 
-pub fn part2(input: &TreeChart) {
-    let answer: usize = [(1, 1), (1, 3), (1, 5), (1, 7), (2, 1)]
+pub fn part2(input: &TreeChart) -> Result<usize, TreeChartError> {
+    [(1, 1), (1, 3), (1, 5), (1, 7), (2, 1)]
         .iter()
         .map(|offset| count_trees_encountered(input, *offset))
-        .product();
-    println!("Answer: {}", answer);
+        .product()
+}
+
+pub struct Day3;
+
+impl<'a> Solution<'a> for Day3 {
+    type Input = TreeChart;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(raw: &'a str) -> Result<Self::Input, Error> {
+        Ok(input(raw)?)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        Ok(part1(input)?)
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Ok(part2(input)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#
+";
+
+    #[test]
+    fn example_test() {
+        let chart = input(EXAMPLE).unwrap();
+        assert_eq!(part1(&chart).unwrap(), 7);
+        assert_eq!(part2(&chart).unwrap(), 336);
+        assert_eq!(chart.grid().height(), 11);
+        assert_eq!(chart.get((1, 11)), Some((true, (1, 0))));
+        assert_eq!(chart.get((usize::MAX, usize::MAX)), None);
+    }
+
+    #[test]
+    fn slope_test() {
+        // #..
+        // ..#
+        // .#.
+        let chart = input("#..\n..#\n.#.\n").unwrap();
+        let walk = |start, offset| {
+            chart
+                .iter_from(start, offset)
+                .unwrap()
+                .collect::<Vec<_>>()
+        };
+
+        // Going left off the edge comes back on the right
+        assert_eq!(count_trees_encountered(&chart, (1, -1)).unwrap(), 3);
+        assert_eq!(walk((0, 1), (1, -2)), vec![false, true, false]);
+        // Going up starts from the bottom row by default
+        assert_eq!(chart.iter((-1, 1)).unwrap().count(), 3);
+        assert_eq!(walk((2, 1), (-1, 1)), vec![true, true, true]);
+        assert_eq!(walk((1, 5), (-1, 0)), vec![true, false]);
+        assert_eq!(walk((0, 0), (2, 0)), vec![true, false]);
+        assert_eq!(walk((3, 0), (1, 1)), vec![]);
+
+        // Extreme steps wrap round or leave the chart rather than overflowing
+        // Both are one more than a multiple of the width
+        assert_eq!(count_trees_encountered(&chart, (1, isize::MAX)).unwrap(), 1);
+        assert_eq!(count_trees_encountered(&chart, (1, isize::MIN)).unwrap(), 1);
+        assert_eq!(walk((1, 0), (isize::MAX, 1)), vec![false]);
+        assert_eq!(walk((1, 0), (isize::MIN, 1)), vec![false]);
+        assert_eq!(walk((2, usize::MAX - 2), (-1, isize::MIN)), vec![true, true, true]);
+        assert_eq!(walk((usize::MAX, 0), (1, 1)), vec![]);
+
+        assert!(matches!(
+            chart.iter((0, 1)),
+            Err(TreeChartError::ZeroRowStep)
+        ));
+    }
+
+    #[test]
+    fn validation_test() {
+        match input("..#\n.#\n#..\n") {
+            Err(e @ TreeChartError::RaggedRow { .. }) => {
+                assert_eq!(e.to_string(), "Row 2 is 2 wide, but the first row is 3");
+                assert_eq!(e.location(), Some(Location { line: 2, column: 3 }));
+            }
+            other => panic!("expected a ragged row, got {:?}", other.err()),
+        }
+        // Rows that would still divide evenly into the width
+        assert!(matches!(
+            input("..#.\n.#\n#.\n"),
+            Err(TreeChartError::RaggedRow {
+                row: 2,
+                expected: 4,
+                actual: 2
+            })
+        ));
+
+        match input("..#\n.X.\n") {
+            Err(e @ TreeChartError::UnexpectedCharacter { .. }) => {
+                assert_eq!(e.location(), Some(Location { line: 2, column: 2 }));
+            }
+            other => panic!("expected an unexpected character, got {:?}", other.err()),
+        }
+        assert!(matches!(input(""), Err(TreeChartError::WidthUnset)));
+
+        let alphabet = Alphabet {
+            open: ' ',
+            tree: 'T',
+        };
+        let chart = input_with_alphabet(" T\nT \n", alphabet).unwrap();
+        assert_eq!(chart.grid().row(1), Some(&[true, false][..]));
+        assert!(input_with_alphabet("..\n", alphabet).is_err());
+    }
 }
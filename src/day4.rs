@@ -1,8 +1,8 @@
 use std::collections::HashMap;
 
-use anyhow::anyhow;
+use crate::{solution::Solution, Error, Location};
 
-pub fn input<'a>(input: &'a str) -> Result<Vec<HashMap<&'a str, &'a str>>, anyhow::Error> {
+pub fn input(input: &str) -> Result<Vec<HashMap<&str, &str>>, Error> {
     input
         .split("\n\n")
         .map(|section| {
@@ -13,25 +13,28 @@ pub fn input<'a>(input: &'a str) -> Result<Vec<HashMap<&'a str, &'a str>>, anyho
                         .find(':')
                         .map(|idx| entry.split_at(idx))
                         .map(|(key, value)| (key, &value[1..]))
+                        .ok_or_else(|| Error::Day4 {
+                            location: Location::of(input, entry),
+                            message: format!("expected a `key:value` field, got {:?}", entry),
+                        })
                 })
-                .collect::<Option<HashMap<&'a str, &'a str>>>()
+                .collect::<Result<HashMap<&str, &str>, _>>()
         })
-        .collect::<Option<Vec<_>>>()
-        .ok_or(anyhow!("Error collecting input"))
+        .collect::<Result<Vec<_>, _>>()
 }
 
-pub fn part1<'a>(input: &[HashMap<&'a str, &'a str>]) {
+pub fn part1(input: &[HashMap<&str, &str>]) -> usize {
     let required_fields = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
-    let answer = input
+    input
         .iter()
         .filter(|passport| required_fields.iter().all(|rf| passport.contains_key(rf)))
-        .count();
-    println!("# of passports meeting requirements: {}", answer);
+        .count()
 }
 
 fn validate_year(input: &str, min: usize, max: usize) -> bool {
     input.len() == 4
-        && usize::from_str_radix(input, 10)
+        && input
+            .parse::<usize>()
             .map(|v| min <= v && v <= max)
             .unwrap_or(false)
 }
@@ -47,11 +50,11 @@ fn validate_height(input: &&str) -> bool {
             return false;
         }
 
-        if let Ok(height) = u32::from_str_radix(number, 10) {
+        if let Ok(height) = number.parse::<u32>() {
             if suffix == "cm" {
-                150 <= height && height <= 193
+                (150..=193).contains(&height)
             } else if suffix == "in" {
-                59 <= height && height <= 76
+                (59..=76).contains(&height)
             } else {
                 false
             }
@@ -63,9 +66,9 @@ fn validate_height(input: &&str) -> bool {
     }
 }
 
-pub fn part2<'a>(input: &[HashMap<&'a str, &'a str>]) {
+pub fn part2(input: &[HashMap<&str, &str>]) -> usize {
     let valid_eyecolors = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
-    let answer = input
+    input
         .iter()
         .filter(|passport| {
             passport
@@ -98,6 +101,25 @@ pub fn part2<'a>(input: &[HashMap<&'a str, &'a str>]) {
                     .map(|v| v.len() == 9 && v.chars().all(|c| c.is_ascii_digit()))
                     .unwrap_or(false)
         })
-        .count();
-    println!("# of passports meeting requirements: {}", answer);
+        .count()
+}
+
+pub struct Day4;
+
+impl<'a> Solution<'a> for Day4 {
+    type Input = Vec<HashMap<&'a str, &'a str>>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(raw: &'a str) -> Result<Self::Input, Error> {
+        input(raw)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        Ok(part1(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Ok(part2(input))
+    }
 }
@@ -1,3 +1,5 @@
+use crate::{solution::Solution, Error, Location};
+
 pub enum RowPartition {
     Front,
     Back,
@@ -28,17 +30,18 @@ impl<T> Iterator for RowPartitionIterator<T>
 where
     T: Iterator<Item = char>,
 {
-    type Item = RowPartition;
+    /// Err holds any character other than F or B
+    type Item = Result<RowPartition, char>;
 
     fn next(&mut self) -> Option<Self::Item> {
         match self.characters.next() {
             Some(letter) => {
                 if letter == 'F' {
-                    Some(RowPartition::Front)
+                    Some(Ok(RowPartition::Front))
                 } else if letter == 'B' {
-                    Some(RowPartition::Back)
+                    Some(Ok(RowPartition::Back))
                 } else {
-                    panic!("Unexpected character {}", letter)
+                    Some(Err(letter))
                 }
             }
             None => None,
@@ -66,17 +69,18 @@ impl<T> Iterator for SeatPartitionIterator<T>
 where
     T: Iterator<Item = char>,
 {
-    type Item = SeatPartition;
+    /// Err holds any character other than L or R
+    type Item = Result<SeatPartition, char>;
 
     fn next(&mut self) -> Option<Self::Item> {
         match self.characters.next() {
             Some(letter) => {
                 if letter == 'L' {
-                    Some(SeatPartition::Left)
+                    Some(Ok(SeatPartition::Left))
                 } else if letter == 'R' {
-                    Some(SeatPartition::Right)
+                    Some(Ok(SeatPartition::Right))
                 } else {
-                    panic!("Unexpected character {}", letter)
+                    Some(Err(letter))
                 }
             }
             None => None,
@@ -124,50 +128,118 @@ impl PartitionRange {
     }
 }
 
-pub fn get_seat_id(boarding_pass: &str) -> usize {
+pub fn get_seat_id(boarding_pass: &str) -> Result<usize, Error> {
+    locate_seat_id(boarding_pass, boarding_pass)
+}
+
+/// Decodes `boarding_pass`, a slice of `input`, reporting errors at their place in `input`
+fn locate_seat_id(input: &str, boarding_pass: &str) -> Result<usize, Error> {
+    let error_at = |char_idx: usize, message: String| {
+        let byte_idx = boarding_pass
+            .char_indices()
+            .nth(char_idx)
+            .map(|(idx, _)| idx)
+            .unwrap_or_else(|| boarding_pass.len());
+        Error::Day5 {
+            location: Location::of(input, &boarding_pass[byte_idx..]),
+            message,
+        }
+    };
+
+    let length = boarding_pass.chars().count();
+    if length != 10 {
+        return Err(error_at(
+            length.min(10),
+            format!("boarding pass should be 10 characters, got {}", length),
+        ));
+    }
+
     let mut row = PartitionRange::new(0, 127);
-    for row_partition in RowPartitionIterator::new(boarding_pass.chars().take(7)) {
+    let row_partitions = RowPartitionIterator::new(boarding_pass.chars().take(7));
+    for (idx, row_partition) in row_partitions.enumerate() {
         match row_partition {
-            RowPartition::Front => row.lower_half(),
-            RowPartition::Back => row.upper_half(),
+            Ok(RowPartition::Front) => row.lower_half(),
+            Ok(RowPartition::Back) => row.upper_half(),
+            Err(c) => return Err(error_at(idx, format!("expected F or B, got {:?}", c))),
         };
     }
     let row = row.get_converged_value().unwrap();
 
     let mut col = PartitionRange::new(0, 7);
-    for seat_partition in SeatPartitionIterator::new(boarding_pass.chars().skip(7).take(3)) {
+    let seat_partitions = SeatPartitionIterator::new(boarding_pass.chars().skip(7).take(3));
+    for (idx, seat_partition) in seat_partitions.enumerate() {
         match seat_partition {
-            SeatPartition::Left => col.lower_half(),
-            SeatPartition::Right => col.upper_half(),
+            Ok(SeatPartition::Left) => col.lower_half(),
+            Ok(SeatPartition::Right) => col.upper_half(),
+            Err(c) => return Err(error_at(7 + idx, format!("expected L or R, got {:?}", c))),
         };
     }
     let col = col.get_converged_value().unwrap();
 
-    let seat_id = row * 8 + col;
-    seat_id
+    Ok(row * 8 + col)
 }
 
-pub fn part1(input: &str) {
-    let highest_seat_id = input.lines().map(get_seat_id).max().unwrap();
+/// Decodes the seat id of every boarding pass, one per line
+pub fn input(input: &str) -> Result<Vec<usize>, Error> {
+    input
+        .lines()
+        .map(|boarding_pass| locate_seat_id(input, boarding_pass))
+        .collect::<Result<Vec<_>, _>>()
+}
 
-    println!("{}", highest_seat_id);
+/// Returns the highest seat id on a boarding pass, or None if there are no boarding passes
+pub fn part1(seat_ids: &[usize]) -> Option<usize> {
+    seat_ids.iter().cloned().max()
 }
 
-pub fn part2(input: &str) {
-    let mut seat_ids = input.lines().map(get_seat_id).collect::<Vec<_>>();
+/// Returns the id of the one missing seat whose neighbours are both taken, if there is one
+pub fn part2(seat_ids: &[usize]) -> Option<usize> {
+    let mut seat_ids = seat_ids.to_vec();
     seat_ids.sort();
 
-    let seat_id = seat_ids
+    seat_ids
         .windows(2)
         .find(|window| window[0] + 2 == window[1])
-        .map(|window| window[1] - 1);
+        .map(|window| window[1] - 1)
+}
+
+pub struct Day5;
+
+impl<'a> Solution<'a> for Day5 {
+    type Input = Vec<usize>;
+    type Answer1 = Option<usize>;
+    type Answer2 = Option<usize>;
 
-    println!("Seat id = {:?}", seat_id);
+    fn parse(raw: &'a str) -> Result<Self::Input, Error> {
+        input(raw)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        Ok(part1(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Ok(part2(input))
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use super::PartitionRange;
+    use super::{get_seat_id, PartitionRange};
+
+    #[test]
+    fn seat_id_test() {
+        assert_eq!(get_seat_id("FBFBBFFRLR").unwrap(), 357);
+        assert_eq!(get_seat_id("BFFFBBFRRR").unwrap(), 567);
+
+        let error = get_seat_id("FBFBXFFRLR").unwrap_err();
+        assert_eq!(error.location().unwrap().column, 5);
+        let error = get_seat_id("FBFBBFFRLF").unwrap_err();
+        assert_eq!(error.location().unwrap().column, 10);
+        let error = get_seat_id("FBFBBFF").unwrap_err();
+        assert_eq!(error.location().unwrap().column, 8);
+    }
+
     #[test]
     fn range_test() {
         let mut range = PartitionRange::new(0, 127);
@@ -1,7 +1,23 @@
 use std::collections::HashMap;
 
-pub fn part1(input: &str) {
-    let sum: usize = input
+use crate::{solution::Solution, Error, Location};
+
+/// Checks the input only holds answered questions (letters) and whitespace
+pub fn input(input: &str) -> Result<&str, Error> {
+    match input
+        .char_indices()
+        .find(|(_, c)| !c.is_alphabetic() && !c.is_whitespace())
+    {
+        Some((idx, character)) => Err(Error::Day6 {
+            location: Location::of(input, &input[idx..]),
+            character,
+        }),
+        None => Ok(input),
+    }
+}
+
+pub fn part1(input: &str) -> usize {
+    input
         .split("\n\n")
         .map(str::trim)
         .map(|lines| {
@@ -11,12 +27,11 @@ pub fn part1(input: &str) {
                 .collect::<std::collections::HashSet<_>>()
                 .len()
         })
-        .sum();
-    println!("{}", sum);
+        .sum()
 }
 
-pub fn part2(input: &str) {
-    let sum: usize = input
+pub fn part2(input: &str) -> usize {
+    input
         .split("\n\n")
         .map(str::trim)
         .map(|lines| {
@@ -27,8 +42,6 @@ pub fn part2(input: &str) {
                         *state.entry(c).or_default() += 1;
                     } else if c.is_whitespace() {
                         num_lines += 1;
-                    } else {
-                        panic!("Unexpected character: {}", c);
                     }
 
                     (state, num_lines)
@@ -37,6 +50,25 @@ pub fn part2(input: &str) {
             let agreed_on_questions = map.iter().filter(|(_, v)| **v == num_lines).count();
             agreed_on_questions
         })
-        .sum();
-    println!("{}", sum);
+        .sum()
+}
+
+pub struct Day6;
+
+impl<'a> Solution<'a> for Day6 {
+    type Input = &'a str;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(raw: &'a str) -> Result<Self::Input, Error> {
+        input(raw)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        Ok(part1(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Ok(part2(input))
+    }
 }
@@ -1,16 +1,17 @@
 use std::collections::HashMap;
 
-use anyhow::anyhow;
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_until},
-    character::complete::multispace1,
-    combinator::iterator,
+    character::complete::{digit1, multispace1},
+    combinator::{iterator, map_res},
     sequence::{preceded, terminated},
 };
 use petgraph::graphmap::DiGraphMap;
 use petgraph::visit::Walker;
 
+use crate::{solution::Solution, Error, Location};
+
 type CountType = u64;
 
 #[derive(Debug)]
@@ -19,9 +20,8 @@ struct Insides<'a> {
     color: &'a str,
 }
 
-fn get_insides(input: &str) -> nom::IResult<&str, Insides> {
-    let (rest, number) = nom::character::complete::digit1(input)?;
-    let number = CountType::from_str_radix(number, 10).unwrap();
+fn get_insides(input: &str) -> nom::IResult<&str, Insides<'_>> {
+    let (rest, number) = map_res(digit1, str::parse::<CountType>)(input)?;
 
     let (rest, color) = preceded(
         multispace1,
@@ -37,17 +37,20 @@ fn get_insides(input: &str) -> nom::IResult<&str, Insides> {
 }
 
 fn get_color(input: &str) -> nom::IResult<&str, &str> {
-    const BAG_MIDDLE: &'static str = " bags contain ";
+    const BAG_MIDDLE: &str = " bags contain ";
     let (rest, color) = terminated(take_until(BAG_MIDDLE), tag(BAG_MIDDLE))(input)?;
 
     Ok((rest, color))
 }
 
-pub fn input(input: &str) -> Result<DiGraphMap<&str, CountType>, anyhow::Error> {
+pub fn input(input: &str) -> Result<DiGraphMap<&str, CountType>, Error> {
     let mut graph = DiGraphMap::new();
 
     for line in input.lines() {
-        let (rest, color) = get_color(line).map_err(|e| anyhow!("{}", e))?;
+        let (rest, color) = get_color(line).map_err(|_| Error::Day7 {
+            location: Location::of(input, line),
+            message: "expected a rule like `<color> bags contain ...`".to_string(),
+        })?;
         graph.add_node(color);
 
         let mut inside_iterator =
@@ -56,18 +59,34 @@ pub fn input(input: &str) -> Result<DiGraphMap<&str, CountType>, anyhow::Error>
             graph.add_node(inside.color);
             graph.add_edge(color, inside.color, inside.count);
         }
+
+        let rest = match inside_iterator.finish() {
+            Ok((rest, ())) => rest,
+            Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => e.input,
+            Err(nom::Err::Incomplete(_)) => &line[line.len()..],
+        };
+        if !rest.is_empty() && rest != "no other bags." {
+            return Err(Error::Day7 {
+                location: Location::of(input, rest),
+                message: format!(
+                    "expected `<count> <color> bags` or `no other bags.`, got {:?}",
+                    rest
+                ),
+            });
+        }
     }
 
     Ok(graph)
 }
 
-pub fn part1(input: &DiGraphMap<&str, CountType>) {
+pub fn part1(input: &DiGraphMap<&str, CountType>) -> usize {
     let reversed_graph = petgraph::visit::Reversed(input);
     let dfs = petgraph::visit::Dfs::new(reversed_graph, "shiny gold");
-    println!("Count = {}", dfs.iter(reversed_graph).count() - 1); // -1 so we don't count shiny gold itself
+    dfs.iter(reversed_graph).count() - 1 // -1 so we don't count shiny gold itself
 }
 
-pub fn part2(input: &DiGraphMap<&str, CountType>) {
+/// Returns how many bags a shiny gold bag holds, or None if there is no shiny gold bag
+pub fn part2(input: &DiGraphMap<&str, CountType>) -> Option<CountType> {
     let reversed_graph = petgraph::visit::Reversed(input);
     let topo = petgraph::visit::Topo::new(reversed_graph);
     let mut bag_counts = HashMap::new();
@@ -83,24 +102,47 @@ pub fn part2(input: &DiGraphMap<&str, CountType>) {
             .sum();
         bag_counts.insert(node, inner_bags);
     }
-    println!("Count = {}", bag_counts.get("shiny gold").unwrap());
+    bag_counts.get("shiny gold").cloned()
 }
 
-pub fn generate_bad_case_for_non_dynamic_programming_solution() {
-    println!("shiny gold bags contain 1 A a bag, 1 A b bag.");
+/// Builds an input where every color holds two bags of the next color, so counting shiny gold's
+/// contents without reusing the counts of inner bags takes 2^52 steps
+pub fn generate_bad_case_for_non_dynamic_programming_solution() -> String {
+    let mut input = String::from("shiny gold bags contain 1 A a bag, 1 A b bag.\n");
     ('A'..='Z')
         .chain('a'..='z')
         .collect::<Vec<_>>()
         .windows(2)
         .for_each(|cs| {
             for color in 'a'..='b' {
-                println!(
-                    "{} {} bags contain 1 {} a bag, 1 {} b bag.",
+                input.push_str(&format!(
+                    "{} {} bags contain 1 {} a bag, 1 {} b bag.\n",
                     cs[0], color, cs[1], cs[1]
-                );
+                ));
             }
         });
     for color in 'a'..='b' {
-        println!("z {} bags contain no other bags.", color);
+        input.push_str(&format!("z {} bags contain no other bags.\n", color));
+    }
+    input
+}
+
+pub struct Day7;
+
+impl<'a> Solution<'a> for Day7 {
+    type Input = DiGraphMap<&'a str, CountType>;
+    type Answer1 = usize;
+    type Answer2 = Option<CountType>;
+
+    fn parse(raw: &'a str) -> Result<Self::Input, Error> {
+        input(raw)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        Ok(part1(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Ok(part2(input))
     }
 }
@@ -0,0 +1,110 @@
+use std::{fmt, io, num::ParseIntError};
+
+use crate::{
+    day1::{self, Entry},
+    day3::TreeChartError,
+};
+
+/// A 1-based line and column (in characters) within a puzzle input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    /// Locates the start of `part`, which must be a slice of `input`
+    pub fn of(input: &str, part: &str) -> Self {
+        let offset = part.as_ptr() as usize - input.as_ptr() as usize;
+        assert!(offset <= input.len(), "part should be a slice of input");
+
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+        Location {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Day 1, {location}: invalid expense entry: {source}")]
+    Day1 {
+        location: Location,
+        source: ParseIntError,
+    },
+
+    #[error("Day 1: the product of {} overflows", day1::describe(.entries))]
+    Day1Overflow { entries: Vec<Entry> },
+
+    #[error("Day 1: could not stream the expense report: {source}")]
+    Day1Io { source: io::Error },
+
+    #[error("Day 2, {location}: {message}")]
+    Day2 { location: Location, message: String },
+
+    #[error("Day 3: {0}")]
+    Day3(#[from] TreeChartError),
+
+    #[error("Day 4, {location}: {message}")]
+    Day4 { location: Location, message: String },
+
+    #[error("Day 5, {location}: {message}")]
+    Day5 { location: Location, message: String },
+
+    #[error("Day 6, {location}: unexpected character {character:?}")]
+    Day6 { location: Location, character: char },
+
+    #[error("Day 7, {location}: {message}")]
+    Day7 { location: Location, message: String },
+}
+
+impl Error {
+    /// Where in the input the error was found, if it points at a particular place
+    pub fn location(&self) -> Option<Location> {
+        match self {
+            Error::Day1 { location, .. }
+            | Error::Day2 { location, .. }
+            | Error::Day4 { location, .. }
+            | Error::Day5 { location, .. }
+            | Error::Day6 { location, .. }
+            | Error::Day7 { location, .. } => Some(*location),
+            Error::Day3(e) => e.location(),
+            Error::Day1Overflow { .. } | Error::Day1Io { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Location;
+
+    #[test]
+    fn location_test() {
+        let input = "abc\nd€f\n";
+        assert_eq!(Location::of(input, input), Location { line: 1, column: 1 });
+        assert_eq!(
+            Location::of(input, &input[2..]),
+            Location { line: 1, column: 3 }
+        );
+        assert_eq!(
+            Location::of(input, &input[4..]),
+            Location { line: 2, column: 1 }
+        );
+        assert_eq!(
+            Location::of(input, &input[8..]),
+            Location { line: 2, column: 3 }
+        );
+        assert_eq!(
+            Location::of(input, &input[input.len()..]),
+            Location { line: 3, column: 1 }
+        );
+    }
+}
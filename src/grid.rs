@@ -0,0 +1,276 @@
+use std::ops::Index;
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum GridError {
+    #[error("The width should be set to something other than 0")]
+    ZeroWidth,
+
+    #[error("Number of cells {cells} indivisible by width {width}")]
+    Indivisible { cells: usize, width: usize },
+}
+
+/// Which edges of a grid lead back round to the opposite edge
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Wrap {
+    #[default]
+    None,
+    /// Moving off the left or right edge comes back on the other side
+    Horizontal,
+    /// Moving off the top or bottom edge comes back on the other side
+    Vertical,
+    Both,
+}
+
+impl Wrap {
+    fn horizontal(self) -> bool {
+        matches!(self, Wrap::Horizontal | Wrap::Both)
+    }
+
+    fn vertical(self) -> bool {
+        matches!(self, Wrap::Vertical | Wrap::Both)
+    }
+}
+
+/// Which cells count as neighbours
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Up, down, left and right
+    Four,
+    /// Diagonals as well
+    Eight,
+}
+
+impl Connectivity {
+    fn offsets(self) -> &'static [(isize, isize)] {
+        const EIGHT: &[(isize, isize)] = &[
+            (-1, 0),
+            (0, -1),
+            (0, 1),
+            (1, 0),
+            (-1, -1),
+            (-1, 1),
+            (1, -1),
+            (1, 1),
+        ];
+        match self {
+            Connectivity::Four => &EIGHT[..4],
+            Connectivity::Eight => EIGHT,
+        }
+    }
+}
+
+/// A rectangular grid of cells stored row by row, addressed as (row, column) from the top left
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    wrap: Wrap,
+}
+
+impl<T> Grid<T> {
+    /// A grid of `cells` read row by row, `width` to a row, that doesn't wrap
+    pub fn new(cells: Vec<T>, width: usize) -> Result<Self, GridError> {
+        if width == 0 {
+            return Err(GridError::ZeroWidth);
+        }
+        if !cells.len().is_multiple_of(width) {
+            return Err(GridError::Indivisible {
+                cells: cells.len(),
+                width,
+            });
+        }
+
+        Ok(Grid {
+            cells,
+            width,
+            wrap: Wrap::None,
+        })
+    }
+
+    pub fn with_wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.len() / self.width
+    }
+
+    pub fn wrap(&self) -> Wrap {
+        self.wrap
+    }
+
+    /// Where (row, column) lands once wrapped, or None if it's off an edge that doesn't wrap
+    pub fn resolve(&self, (row, column): (isize, isize)) -> Option<(usize, usize)> {
+        let resolve = |index: isize, len: usize, wraps: bool| {
+            if wraps {
+                Some(index.rem_euclid(len as isize) as usize)
+            } else if 0 <= index && (index as usize) < len {
+                Some(index as usize)
+            } else {
+                None
+            }
+        };
+
+        if self.cells.is_empty() {
+            return None;
+        }
+        Some((
+            resolve(row, self.height(), self.wrap.vertical())?,
+            resolve(column, self.width, self.wrap.horizontal())?,
+        ))
+    }
+
+    /// The cell at (row, column), wrapping around the edges that wrap
+    pub fn get(&self, (row, column): (usize, usize)) -> Option<&T> {
+        let row = if self.wrap.vertical() && self.height() > 0 {
+            row % self.height()
+        } else {
+            row
+        };
+        let column = if self.wrap.horizontal() {
+            column % self.width
+        } else {
+            column
+        };
+
+        if column >= self.width {
+            return None;
+        }
+        self.cells
+            .get(row.checked_mul(self.width)?.checked_add(column)?)
+    }
+
+    pub fn row(&self, row: usize) -> Option<&[T]> {
+        self.cells.chunks(self.width).nth(row)
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width)
+    }
+
+    /// The cells of `column` from top to bottom, None if it's past the right edge or the grid is empty
+    pub fn column(&self, column: usize) -> Option<impl Iterator<Item = &T>> {
+        if column >= self.width || self.cells.is_empty() {
+            return None;
+        }
+        Some(self.cells[column..].iter().step_by(self.width))
+    }
+
+    /// The cells next to (row, column) and where they are, wrapping around the edges that wrap.
+    ///
+    /// On grids narrow enough to wrap onto themselves the same cell can be reached more than once.
+    pub fn neighbours(
+        &self,
+        (row, column): (usize, usize),
+        connectivity: Connectivity,
+    ) -> impl Iterator<Item = ((usize, usize), &T)> {
+        connectivity
+            .offsets()
+            .iter()
+            .filter_map(move |&(row_step, column_step)| {
+                let position =
+                    self.resolve((row as isize + row_step, column as isize + column_step))?;
+                Some((position, &self[position]))
+            })
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    /// Panics if (row, column) is outside the grid, even on edges that wrap
+    fn index(&self, (row, column): (usize, usize)) -> &Self::Output {
+        assert!(column < self.width, "column {} is out of bounds", column);
+        &self.cells[row * self.width + column]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 0 1 2
+    // 3 4 5
+    fn grid(wrap: Wrap) -> Grid<u32> {
+        Grid::new((0..6).collect(), 3).unwrap().with_wrap(wrap)
+    }
+
+    #[test]
+    fn new_test() {
+        assert_eq!(grid(Wrap::None).height(), 2);
+        assert_eq!(
+            Grid::new(vec![0; 5], 3),
+            Err(GridError::Indivisible { cells: 5, width: 3 })
+        );
+        assert_eq!(Grid::new(vec![0; 5], 0), Err(GridError::ZeroWidth));
+    }
+
+    #[test]
+    fn wrap_test() {
+        assert_eq!(grid(Wrap::None).get((1, 3)), None);
+        assert_eq!(grid(Wrap::Horizontal).get((1, 3)), Some(&3));
+        assert_eq!(grid(Wrap::Horizontal).get((2, 0)), None);
+        assert_eq!(grid(Wrap::Vertical).get((2, 0)), Some(&0));
+        assert_eq!(grid(Wrap::Both).get((5, 7)), Some(&4));
+        assert_eq!(grid(Wrap::None).get((usize::MAX, 0)), None);
+        assert_eq!(
+            grid(Wrap::Horizontal).get((usize::MAX / 2, usize::MAX)),
+            None
+        );
+        assert_eq!(grid(Wrap::Vertical).get((usize::MAX, 0)), Some(&3));
+
+        assert_eq!(grid(Wrap::None).resolve((-1, 0)), None);
+        assert_eq!(grid(Wrap::Horizontal).resolve((0, -1)), Some((0, 2)));
+        assert_eq!(grid(Wrap::Vertical).resolve((-1, 0)), Some((1, 0)));
+        assert_eq!(grid(Wrap::Both).resolve((-3, -4)), Some((1, 2)));
+    }
+
+    #[test]
+    fn rows_and_columns_test() {
+        let grid = grid(Wrap::None);
+        assert_eq!(grid.row(1), Some(&[3, 4, 5][..]));
+        assert_eq!(grid.row(2), None);
+        assert_eq!(grid.rows().count(), 2);
+        assert_eq!(grid.column(2).unwrap().collect::<Vec<_>>(), vec![&2, &5]);
+        assert!(grid.column(3).is_none());
+        assert_eq!(grid[(1, 0)], 3);
+
+        let empty = Grid::<u32>::new(vec![], 3).unwrap();
+        assert_eq!(empty.height(), 0);
+        assert_eq!(empty.rows().count(), 0);
+        assert_eq!(empty.row(0), None);
+        assert!(empty.column(0).is_none());
+        assert!(empty.column(1).is_none());
+    }
+
+    #[test]
+    fn neighbours_test() {
+        let values = |grid: &Grid<u32>, connectivity| {
+            let mut values = grid
+                .neighbours((0, 0), connectivity)
+                .map(|(_, &value)| value)
+                .collect::<Vec<_>>();
+            values.sort_unstable();
+            values
+        };
+
+        assert_eq!(values(&grid(Wrap::None), Connectivity::Four), vec![1, 3]);
+        assert_eq!(
+            values(&grid(Wrap::None), Connectivity::Eight),
+            vec![1, 3, 4]
+        );
+        assert_eq!(
+            values(&grid(Wrap::Horizontal), Connectivity::Eight),
+            vec![1, 2, 3, 4, 5]
+        );
+        assert_eq!(
+            values(&grid(Wrap::Both), Connectivity::Four),
+            vec![1, 2, 3, 3]
+        );
+    }
+}
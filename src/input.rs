@@ -0,0 +1,67 @@
+use std::{
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+/// Environment variable overriding the directory day inputs are read from (default: inputs)
+pub const INPUTS_DIR_VAR: &str = "AOC_INPUTS_DIR";
+
+/// Path that reads the input from stdin instead of a file
+pub const STDIN_PATH: &str = "-";
+
+/// The input path for a day when none is given, e.g. inputs/day07.txt
+pub fn default_path(day: u32) -> PathBuf {
+    let dir = std::env::var_os(INPUTS_DIR_VAR)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("inputs"));
+    dir.join(format!("day{:02}.txt", day))
+}
+
+/// Reads an input from `path`, or from stdin if `path` is "-", and normalises it
+pub fn read(path: &Path) -> io::Result<String> {
+    let raw = if path == Path::new(STDIN_PATH) {
+        let mut raw = String::new();
+        io::stdin().read_to_string(&mut raw)?;
+        raw
+    } else {
+        std::fs::read_to_string(path)?
+    };
+
+    Ok(normalize(&raw))
+}
+
+/// Converts CRLF line endings to LF and ends the input with exactly one newline,
+/// so parsers splitting on "\n\n" see the same sections whatever editor saved the file
+pub fn normalize(raw: &str) -> String {
+    let mut input = raw.replace("\r\n", "\n");
+    input.truncate(input.trim_end_matches('\n').len());
+    if !input.is_empty() {
+        input.push('\n');
+    }
+    input
+}
+
+#[cfg(test)]
+mod test {
+    use super::normalize;
+
+    #[test]
+    fn normalize_test() {
+        assert_eq!(normalize("abc\r\n\r\ndef\r\n"), "abc\n\ndef\n");
+        assert_eq!(normalize("abc\n\ndef"), "abc\n\ndef\n");
+        assert_eq!(normalize("abc\n\ndef\n\n\n"), "abc\n\ndef\n");
+        assert_eq!(normalize("\n"), "");
+        assert_eq!(normalize(""), "");
+    }
+
+    #[test]
+    fn crlf_sections_match_test() {
+        let unix = "abc\n\na\nb\nc\n\nab\nac\n\na\na\na\na\n\nb\n";
+        let windows = unix.replace('\n', "\r\n") + "\r\n";
+        assert_eq!(
+            crate::day6::part1(&normalize(&windows)),
+            crate::day6::part1(&normalize(unix))
+        );
+        assert_eq!(crate::day6::part2(&normalize(&windows)), 6);
+    }
+}
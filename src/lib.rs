@@ -1,7 +1,35 @@
-pub mod day1;
-pub mod day2;
-pub mod day3;
-pub mod day4;
-pub mod day5;
-pub mod day6;
-pub mod day7;
+pub mod error;
+pub mod grid;
+pub mod input;
+pub mod runner;
+pub mod scaffold;
+pub mod solution;
+pub mod verify;
+
+pub use error::{Error, Location};
+
+/// Declares each day's module and registers its `Solution` with the runner.
+/// `aoc2020 new-day N` adds new days to the end of the list.
+macro_rules! days {
+    ($($number:literal => $module:ident::$solution:ident,)*) => {
+        $(pub mod $module;)*
+
+        const DAYS: &[runner::Day] = &[
+            $(runner::Day::new(
+                $number,
+                runner::run_solution::<$module::$solution>,
+                runner::bench_solution::<$module::$solution>,
+            ),)*
+        ];
+    };
+}
+
+days! {
+    1 => day1::Day1,
+    2 => day2::Day2,
+    3 => day3::Day3,
+    4 => day4::Day4,
+    5 => day5::Day5,
+    6 => day6::Day6,
+    7 => day7::Day7,
+}
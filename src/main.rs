@@ -1,40 +1,395 @@
-use anyhow::Error;
-use aoc2020::*;
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, Context, Error};
+use aoc2020::day2::{self, Semantics};
+use aoc2020::input;
+use aoc2020::runner::{self, Day, Part, PartAnswer, PartReport};
+use aoc2020::scaffold;
+use aoc2020::verify::{self, Manifest};
+
+const USAGE: &str = "\
+Usage: aoc2020 --day N [--part 1|2|all] [--input PATH] [--time]
+       aoc2020 --day 2 --explain [--part 1|2|all] [--input PATH]
+       aoc2020 --time [--part 1|2|all]
+       aoc2020 --all [--part 1|2|all] [--format text|json]
+       aoc2020 --format json [--day N] [--part 1|2|all] [--input PATH]
+       aoc2020 --verify [--day N] [--manifest PATH]
+       aoc2020 new-day N
+
+Commands:
+    new-day N         Create src/dayN.rs from a template and register it in src/lib.rs
+
+Options:
+    --day N           Day to run
+    --part PART       Part to run: 1, 2 or all (default: all)
+    --input PATH      Puzzle input, - for stdin (default: $AOC_INPUTS_DIR/dayNN.txt,
+                      where AOC_INPUTS_DIR defaults to inputs)
+    --time            Report parse and per-part times, for every day if --day isn't given
+    --all             Solve every day in parallel and print a table of answers and times
+    --format FORMAT   Output format: text, or json for one object per line per day and part,
+                      for every day if --day isn't given (default: text)
+    --verify          Check every day's answers against the manifest
+    --manifest PATH   Expected answers for --verify (default: answers.txt)
+    --explain         Day 2 only: also list each entry whose password fails a part's policy,
+                      with its line and why
+";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err(anyhow!("Format should be text or json, got {}", s)),
+        }
+    }
+}
+
+struct Args {
+    new_day: Option<u32>,
+    day: Option<u32>,
+    part: Part,
+    input: Option<PathBuf>,
+    time: bool,
+    all: bool,
+    format: Format,
+    verify: bool,
+    manifest: String,
+    explain: bool,
+}
+
+fn parse_args() -> Result<Args, Error> {
+    let mut args = pico_args::Arguments::from_env();
+    if args.contains(["-h", "--help"]) {
+        print!("{}", USAGE);
+        std::process::exit(0);
+    }
+
+    let new_day = match args.subcommand()?.as_deref() {
+        Some("new-day") => Some(args.free_from_str()?),
+        Some(command) => return Err(anyhow!("Unknown command {}", command)),
+        None => None,
+    };
+
+    let parsed = Args {
+        new_day,
+        day: args.opt_value_from_str("--day")?,
+        part: args.opt_value_from_str("--part")?.unwrap_or(Part::All),
+        input: args.opt_value_from_str("--input")?,
+        time: args.contains("--time"),
+        all: args.contains("--all"),
+        format: args.opt_value_from_str("--format")?.unwrap_or(Format::Text),
+        verify: args.contains("--verify"),
+        manifest: args
+            .opt_value_from_str("--manifest")?
+            .unwrap_or_else(|| "answers.txt".to_string()),
+        explain: args.contains("--explain"),
+    };
+
+    let remaining = args.finish();
+    if !remaining.is_empty() {
+        return Err(anyhow!("Unexpected arguments: {:?}", remaining));
+    }
+
+    if parsed.all && (parsed.day.is_some() || parsed.input.is_some()) {
+        return Err(anyhow!(
+            "--all runs every day, so can't be given --day or --input"
+        ));
+    }
+
+    if parsed.explain && parsed.day != Some(2) {
+        return Err(anyhow!("--explain is only supported with --day 2"));
+    }
+    if parsed.explain
+        && (parsed.new_day.is_some()
+            || parsed.all
+            || parsed.verify
+            || parsed.format == Format::Json)
+    {
+        return Err(anyhow!(
+            "--explain lists failures under the text answers, so can't be given --all, \
+             --verify, --format json or new-day"
+        ));
+    }
+
+    let runs_every_day =
+        parsed.verify || parsed.time || parsed.all || parsed.format == Format::Json;
+    if parsed.new_day.is_none()
+        && parsed.day.is_none()
+        && (!runs_every_day || parsed.input.is_some())
+    {
+        return Err(anyhow!("the '--day' option must be set"));
+    }
+
+    Ok(parsed)
+}
+
+fn get_day(number: u32) -> Result<&'static Day, Error> {
+    runner::get_day(number).ok_or_else(|| anyhow!("No solution registered for day {}", number))
+}
+
+fn read_input(path: &Path) -> Result<String, Error> {
+    input::read(path).with_context(|| format!("Could not read input file {}", path.display()))
+}
+
+/// Quotes the offending line of `input` under errors that point at a place in it
+fn diagnose(error: Error, input: &str) -> Error {
+    let location = error
+        .downcast_ref::<aoc2020::Error>()
+        .and_then(aoc2020::Error::location);
+    match location.and_then(|location| Some((location, input.lines().nth(location.line - 1)?))) {
+        Some((location, line)) => anyhow!(
+            "{}\n\n    {}\n    {}^",
+            error,
+            line,
+            " ".repeat(location.column - 1)
+        ),
+        None => error,
+    }
+}
+
+fn run(args: &Args, day: &Day, input: &str) -> Result<(), Error> {
+    let run = day.run(input, args.part).map_err(|e| diagnose(e, input))?;
+    if args.time {
+        println!("Day {} parse: {:?}", day.number, run.parse_time);
+    }
+
+    for PartAnswer {
+        part,
+        answer,
+        detail,
+        time,
+    } in run.parts
+    {
+        let answer = match (answer, detail) {
+            (Some(answer), Some(detail)) => format!("{} ({})", answer, detail),
+            (Some(answer), None) => answer,
+            (None, _) => "no answer found".to_string(),
+        };
+        if args.time {
+            println!("Day {} part {}: {} ({:?})", day.number, part, answer, time);
+        } else {
+            println!("Day {} part {}: {}", day.number, part, answer);
+        }
+    }
+
+    Ok(())
+}
+
+/// Lists the day 2 entries failing each selected part's policy, on the lines they came from
+fn explain_day2(part: Part, input: &str) -> Result<(), Error> {
+    let entries = day2::input(input).map_err(|e| diagnose(e.into(), input))?;
+    let entries = day2::numbered(&entries);
+    let semantics = Semantics::default();
+    for &number in part.numbers() {
+        let failures = if number == 1 {
+            day2::failures(&entries, |entry| entry.count_verdict(semantics))
+        } else {
+            day2::failures(&entries, |entry| entry.position_verdict(semantics))
+        };
+        println!("Day 2 part {}: {} failure(s)", number, failures.len());
+        for failure in failures {
+            println!("    {}", failure);
+        }
+    }
+
+    Ok(())
+}
+
+fn run_every_day(args: &Args) -> Result<(), Error> {
+    for day in runner::DAYS.iter() {
+        match input::read(&day.default_input_path()) {
+            Ok(input) => run(args, day, &input)?,
+            Err(_) => eprintln!(
+                "Day {}: skipped, {} not found",
+                day.number,
+                day.default_input_path().display()
+            ),
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs the selected day, or every day in parallel, folding failures into the reports
+fn collect_reports(args: &Args) -> Result<Vec<PartReport>, Error> {
+    let day = match args.day {
+        Some(number) => get_day(number)?,
+        None => return Ok(runner::report_all(args.part)),
+    };
+
+    let path = match &args.input {
+        Some(path) => path.clone(),
+        None => day.default_input_path(),
+    };
+    Ok(match read_input(&path) {
+        Ok(input) => day.report(&input, args.part),
+        Err(e) => PartReport::failed(day.number, args.part, &e),
+    })
+}
+
+fn print_json(reports: &[PartReport]) -> Result<(), Error> {
+    for report in reports {
+        println!("{}", serde_json::to_string(report)?);
+    }
+
+    Ok(())
+}
+
+fn print_table(reports: &[PartReport]) {
+    let format_ms = |ms: Option<f64>| match ms {
+        Some(ms) => format!("{:.3}ms", ms),
+        None => "-".to_string(),
+    };
+    let rows = reports
+        .iter()
+        .map(|report| {
+            let answer = match (&report.answer, &report.error) {
+                (_, Some(error)) => format!("error: {}", error),
+                (Some(answer), None) => answer.clone(),
+                (None, None) => "no answer found".to_string(),
+            };
+            (
+                report,
+                answer,
+                format_ms(report.parse_ms),
+                format_ms(report.time_ms),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let answer_width = rows
+        .iter()
+        .map(|(_, answer, _, _)| answer.len())
+        .chain(std::iter::once("Answer".len()))
+        .max()
+        .unwrap();
+    println!(
+        "Day  Part  {:<width$}  {:>10}  {:>10}",
+        "Answer",
+        "Parse",
+        "Time",
+        width = answer_width
+    );
+    for (report, answer, parse, time) in rows {
+        println!(
+            "{:>3}  {:>4}  {:<width$}  {:>10}  {:>10}",
+            report.day,
+            report.part,
+            answer,
+            parse,
+            time,
+            width = answer_width
+        );
+    }
+}
+
+fn verify(args: Args) -> Result<(), Error> {
+    let manifest = std::fs::read_to_string(&args.manifest)
+        .with_context(|| format!("Could not read manifest {}", args.manifest))?;
+    let manifest = Manifest::parse(&manifest)?;
+
+    let days = match args.day {
+        Some(number) => vec![get_day(number)?],
+        None => runner::DAYS.iter().collect(),
+    };
+
+    let mut failures = 0;
+    for day in days {
+        let path = match &args.input {
+            Some(path) => path.clone(),
+            None => day.default_input_path(),
+        };
+        let input = input::read(&path).ok();
+
+        for (part, verdict) in verify::verify(day, &manifest, input.as_deref()) {
+            if verdict.is_failure() {
+                failures += 1;
+            }
+            println!("Day {} part {}: {}", day.number, part, verdict);
+        }
+    }
+
+    if failures > 0 {
+        return Err(anyhow!("{} part(s) failed verification", failures));
+    }
+
+    Ok(())
+}
+
+/// Scaffolds src/dayN.rs and registers it, run from the crate root
+fn new_day(number: u32) -> Result<(), Error> {
+    let lib_path = Path::new("src/lib.rs");
+    let module_path = PathBuf::from(format!("src/day{}.rs", number));
+    if module_path.exists() {
+        return Err(anyhow!("{} already exists", module_path.display()));
+    }
+
+    let lib_rs = std::fs::read_to_string(lib_path)
+        .context("Could not read src/lib.rs, new-day should be run from the crate root")?;
+    let lib_rs = scaffold::register(&lib_rs, number)?;
+
+    std::fs::write(&module_path, scaffold::day_module(number))
+        .with_context(|| format!("Could not write {}", module_path.display()))?;
+    std::fs::write(lib_path, lib_rs).context("Could not write src/lib.rs")?;
+
+    println!(
+        "Created {} and registered day {} in src/lib.rs",
+        module_path.display(),
+        number
+    );
+    Ok(())
+}
 
 fn main() -> Result<(), Error> {
-    // let input = std::fs::read_to_string("inputs/day01.txt")?;
-    // let input = day1::input(&input)?;
-    // day1::part1(&input)?;
-    // day1::part2(&input)?;
-
-    // let input = std::fs::read_to_string("inputs/day02.txt")?;
-    // let input = day2::input(&input).unwrap();
-    // day2::part1(&input);
-    // day2::part2(&input);
-
-    // let input = std::fs::read_to_string("inputs/day03.txt")?;
-    // let input = day3::input(&input).unwrap();
-    // day3::part1(&input);
-    // day3::part2(&input);
-    // Ok(())
-
-    // let input = std::fs::read_to_string("inputs/day04.txt")?;
-    // let input = day4::input(&input).unwrap();
-    // day4::part1(&input);
-    // day4::part2(&input);
-
-    // let input = std::fs::read_to_string("inputs/day05.txt")?;
-    // day5::part1(&input);
-    // day5::part2(&input);
-
-    // let input = std::fs::read_to_string("inputs/day06.txt")?;
-    // day6::part1(&input);
-    // day6::part2(&input);
-
-    let input = std::fs::read_to_string("inputs/day07.txt")?;
-    let input = day7::input(&input).unwrap();
-    day7::part1(&input);
-    day7::part2(&input);
+    let args = parse_args().map_err(|e| anyhow!("{}\n\n{}", e, USAGE))?;
+
+    if let Some(number) = args.new_day {
+        return new_day(number);
+    }
+
+    if args.verify {
+        return verify(args);
+    }
+
+    if args.all || args.format == Format::Json {
+        let reports = collect_reports(&args)?;
+        match args.format {
+            Format::Json => print_json(&reports)?,
+            Format::Text => print_table(&reports),
+        }
+
+        let failures = reports.iter().filter(|r| r.error.is_some()).count();
+        if failures > 0 {
+            return Err(anyhow!("{} part(s) failed", failures));
+        }
+        return Ok(());
+    }
+
+    let day = match args.day {
+        Some(number) => get_day(number)?,
+        None => return run_every_day(&args),
+    };
+
+    let path = match &args.input {
+        Some(path) => path.clone(),
+        None => day.default_input_path(),
+    };
+    let input = read_input(&path)?;
+    run(&args, day, &input)?;
+    if args.explain {
+        explain_day2(args.part, &input)?;
+    }
 
     Ok(())
 }
@@ -0,0 +1,249 @@
+use std::{
+    hint::black_box,
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context, Error};
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::input;
+use crate::solution::{Answer, Solution};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two,
+    All,
+}
+
+impl Part {
+    pub fn includes_part1(self) -> bool {
+        self != Part::Two
+    }
+
+    pub fn includes_part2(self) -> bool {
+        self != Part::One
+    }
+
+    pub fn numbers(self) -> &'static [u32] {
+        match self {
+            Part::One => &[1],
+            Part::Two => &[2],
+            Part::All => &[1, 2],
+        }
+    }
+}
+
+impl FromStr for Part {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Part::One),
+            "2" => Ok(Part::Two),
+            "all" => Ok(Part::All),
+            _ => Err(anyhow!("Part should be one of 1, 2 or all, got {}", s)),
+        }
+    }
+}
+
+/// The rendered answer to one part of a day, None if the part found no answer
+pub struct PartAnswer {
+    pub part: u32,
+    pub answer: Option<String>,
+    /// Where the answer came from, see [`Answer::detail`]
+    pub detail: Option<String>,
+    pub time: Duration,
+}
+
+/// The answers from running a day, along with how long parsing its input took
+pub struct DayRun {
+    pub parse_time: Duration,
+    pub parts: Vec<PartAnswer>,
+}
+
+/// The outcome of one part of a day, with times in milliseconds
+#[derive(Debug, Serialize)]
+pub struct PartReport {
+    pub day: u32,
+    pub part: u32,
+    pub answer: Option<String>,
+    pub detail: Option<String>,
+    pub parse_ms: Option<f64>,
+    pub time_ms: Option<f64>,
+    pub error: Option<String>,
+}
+
+impl PartReport {
+    /// Reports each selected part of a day as failed with `error`
+    pub fn failed(day: u32, part: Part, error: &Error) -> Vec<PartReport> {
+        part.numbers()
+            .iter()
+            .map(|&part| PartReport {
+                day,
+                part,
+                answer: None,
+                detail: None,
+                parse_ms: None,
+                time_ms: None,
+                error: Some(format!("{:#}", error)),
+            })
+            .collect()
+    }
+}
+
+pub struct Day {
+    pub number: u32,
+    run: fn(&str, Part) -> Result<DayRun, Error>,
+    bench: fn(&str, &mut dyn Bencher) -> Result<(), Error>,
+}
+
+impl Day {
+    pub(crate) const fn new(
+        number: u32,
+        run: fn(&str, Part) -> Result<DayRun, Error>,
+        bench: fn(&str, &mut dyn Bencher) -> Result<(), Error>,
+    ) -> Self {
+        Day { number, run, bench }
+    }
+
+    /// Parses `input` and runs the requested part(s) of this day against it
+    pub fn run(&self, input: &str, part: Part) -> Result<DayRun, Error> {
+        (self.run)(input, part)
+    }
+
+    /// Like `run`, but reports an error against each selected part instead of returning it
+    pub fn report(&self, input: &str, part: Part) -> Vec<PartReport> {
+        match self.run(input, part) {
+            Ok(DayRun { parse_time, parts }) => parts
+                .into_iter()
+                .map(|answer| PartReport {
+                    day: self.number,
+                    part: answer.part,
+                    answer: answer.answer,
+                    detail: answer.detail,
+                    parse_ms: Some(parse_time.as_secs_f64() * 1000.0),
+                    time_ms: Some(answer.time.as_secs_f64() * 1000.0),
+                    error: None,
+                })
+                .collect(),
+            Err(e) => PartReport::failed(self.number, part, &e),
+        }
+    }
+
+    /// Hands `bencher` parsing and each part of this day against `input` to time, after checking
+    /// they all succeed
+    pub fn bench(&self, input: &str, bencher: &mut dyn Bencher) -> Result<(), Error> {
+        (self.bench)(input, bencher)
+    }
+
+    /// The input path used when none is given, e.g. inputs/day07.txt
+    pub fn default_input_path(&self) -> PathBuf {
+        input::default_path(self.number)
+    }
+}
+
+/// Every registered day, in order; see the `days!` list in lib.rs
+pub const DAYS: &[Day] = crate::DAYS;
+
+pub fn get_day(number: u32) -> Option<&'static Day> {
+    DAYS.iter().find(|day| day.number == number)
+}
+
+/// Runs every day against its default input concurrently on the rayon thread pool.
+///
+/// A day that fails, including one whose input can't be read, is reported as failed without
+/// affecting the others. Reports are in day then part order.
+pub fn report_all(part: Part) -> Vec<PartReport> {
+    DAYS.par_iter()
+        .flat_map_iter(|day| {
+            let path = day.default_input_path();
+            match input::read(&path)
+                .with_context(|| format!("Could not read input file {}", path.display()))
+            {
+                Ok(input) => day.report(&input, part),
+                Err(e) => PartReport::failed(day.number, part, &e),
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn run_solution<S>(input: &str, part: Part) -> Result<DayRun, Error>
+where
+    S: for<'a> Solution<'a>,
+{
+    let start = Instant::now();
+    let input = S::parse(input)?;
+    let parse_time = start.elapsed();
+
+    let mut parts = Vec::new();
+    if part.includes_part1() {
+        let start = Instant::now();
+        let answer = S::part1(&input)?;
+        parts.push(PartAnswer {
+            part: 1,
+            answer: answer.render(),
+            detail: answer.detail(),
+            time: start.elapsed(),
+        });
+    }
+    if part.includes_part2() {
+        let start = Instant::now();
+        let answer = S::part2(&input)?;
+        parts.push(PartAnswer {
+            part: 2,
+            answer: answer.render(),
+            detail: answer.detail(),
+            time: start.elapsed(),
+        });
+    }
+    Ok(DayRun { parse_time, parts })
+}
+
+/// Something that times a routine run many times over, e.g. a criterion benchmark group
+pub trait Bencher {
+    fn bench(&mut self, name: &str, routine: &mut dyn FnMut());
+}
+
+pub(crate) fn bench_solution<S>(input: &str, bencher: &mut dyn Bencher) -> Result<(), Error>
+where
+    S: for<'a> Solution<'a>,
+{
+    let parsed = S::parse(input)?;
+    S::part1(&parsed)?;
+    S::part2(&parsed)?;
+
+    bencher.bench("parse", &mut || {
+        let _ = black_box(S::parse(black_box(input)));
+    });
+    bencher.bench("part1", &mut || {
+        let _ = black_box(S::part1(black_box(&parsed)));
+    });
+    bencher.bench("part2", &mut || {
+        let _ = black_box(S::part2(black_box(&parsed)));
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{get_day, Part};
+
+    #[test]
+    fn report_test() {
+        let day = get_day(5).unwrap();
+
+        let reports = day.report("FBFBBFFRLR\nBFFFBBFRRR\n", Part::One);
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].answer.as_deref(), Some("567"));
+        assert!(reports[0].error.is_none());
+
+        let reports = day.report("FBFBBFFRLR\nBFFFBBFRRX\n", Part::All);
+        assert_eq!(reports.len(), 2);
+        assert!(reports.iter().all(|r| r.answer.is_none()
+            && r.error.as_deref() == Some("Day 5, line 2, column 10: expected L or R, got 'X'")));
+    }
+}
@@ -0,0 +1,110 @@
+use anyhow::{anyhow, Error};
+
+const TEMPLATE: &str = r#"use crate::{solution::Solution, Error};
+
+pub fn input(input: &str) -> Result<Vec<&str>, Error> {
+    Ok(input.lines().collect())
+}
+
+pub fn part1(input: &[&str]) -> usize {
+    input.len()
+}
+
+pub fn part2(input: &[&str]) -> usize {
+    input.len()
+}
+
+pub struct DayNUMBER;
+
+impl<'a> Solution<'a> for DayNUMBER {
+    type Input = Vec<&'a str>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(raw: &'a str) -> Result<Self::Input, Error> {
+        input(raw)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error> {
+        Ok(part1(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error> {
+        Ok(part2(input))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // The example from the puzzle description
+    const EXAMPLE: &str = "\
+";
+
+    #[test]
+    fn example_test() {
+        let input = input(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 0);
+        assert_eq!(part2(&input), 0);
+    }
+}
+"#;
+
+/// The source of a new src/dayN.rs, a `Solution` skeleton with a test over the example input
+pub fn day_module(day: u32) -> String {
+    TEMPLATE.replace("NUMBER", &day.to_string())
+}
+
+/// Adds `day` to the end of the `days!` list in `lib_rs`, the contents of src/lib.rs
+pub fn register(lib_rs: &str, day: u32) -> Result<String, Error> {
+    const LIST_START: &str = "days! {\n";
+
+    let start = lib_rs
+        .find(LIST_START)
+        .map(|idx| idx + LIST_START.len())
+        .ok_or_else(|| anyhow!("Couldn't find the days! list in lib.rs"))?;
+    let end = start
+        + lib_rs[start..]
+            .find('}')
+            .ok_or_else(|| anyhow!("The days! list in lib.rs isn't closed"))?;
+
+    for entry in lib_rs[start..end].lines() {
+        let number = entry.split("=>").next().unwrap_or_default().trim();
+        if number.parse() == Ok(day) {
+            return Err(anyhow!("Day {} is already registered in lib.rs", day));
+        }
+    }
+
+    Ok(format!(
+        "{}    {} => day{}::Day{},\n{}",
+        &lib_rs[..end],
+        day,
+        day,
+        day,
+        &lib_rs[end..]
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn day_module_test() {
+        let module = day_module(8);
+        assert!(module.contains("pub struct Day8;"));
+        assert!(module.contains("impl<'a> Solution<'a> for Day8 {"));
+    }
+
+    #[test]
+    fn register_test() {
+        let lib_rs = "pub mod runner;\n\ndays! {\n    1 => day1::Day1,\n    7 => day7::Day7,\n}\n";
+        assert_eq!(
+            register(lib_rs, 8).unwrap(),
+            "pub mod runner;\n\ndays! {\n    1 => day1::Day1,\n    7 => day7::Day7,\n    8 => day8::Day8,\n}\n"
+        );
+        assert!(register(lib_rs, 7).is_err());
+        assert!(register("pub mod runner;\n", 8).is_err());
+    }
+}
@@ -0,0 +1,51 @@
+use crate::Error;
+
+/// A day's puzzle: how to parse the raw input, and how to solve each part from the parsed input.
+///
+/// The lifetime lets `Input` borrow from the raw input, e.g. day 7's graph of `&'a str` colors.
+pub trait Solution<'a> {
+    type Input;
+    type Answer1: Answer;
+    type Answer2: Answer;
+
+    fn parse(raw: &'a str) -> Result<Self::Input, Error>;
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, Error>;
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, Error>;
+}
+
+/// A value a part can answer with
+pub trait Answer {
+    /// Renders the answer as it would be submitted, or None if the part found no answer
+    fn render(&self) -> Option<String>;
+
+    /// Where the answer came from, shown alongside it but not part of what's submitted
+    fn detail(&self) -> Option<String> {
+        None
+    }
+}
+
+macro_rules! impl_answer_for_integers {
+    ($($t:ty),*) => {
+        $(
+            impl Answer for $t {
+                fn render(&self) -> Option<String> {
+                    Some(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_answer_for_integers!(u32, u64, usize, i64);
+
+impl<T: Answer> Answer for Option<T> {
+    fn render(&self) -> Option<String> {
+        self.as_ref().and_then(Answer::render)
+    }
+
+    fn detail(&self) -> Option<String> {
+        self.as_ref().and_then(Answer::detail)
+    }
+}
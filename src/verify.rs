@@ -0,0 +1,170 @@
+use std::{collections::HashMap, fmt};
+
+use anyhow::{anyhow, Error};
+
+use crate::runner::{Day, Part};
+
+/// Expected answers per (day, part), read from a manifest such as answers.txt.
+///
+/// Each non-empty line not starting with `#` is `<day> <part> <answer>`.
+#[derive(Debug, Default)]
+pub struct Manifest {
+    expected: HashMap<(u32, u32), String>,
+}
+
+impl Manifest {
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let mut expected = HashMap::new();
+        for (idx, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            let (day, part, answer) = match fields.as_slice() {
+                [day, part, answer] => (day.parse()?, part.parse()?, answer.to_string()),
+                _ => {
+                    return Err(anyhow!(
+                        "Manifest line {} should be `<day> <part> <answer>`, got {:?}",
+                        idx + 1,
+                        line
+                    ))
+                }
+            };
+
+            if expected.insert((day, part), answer).is_some() {
+                return Err(anyhow!(
+                    "Manifest line {} repeats day {} part {}",
+                    idx + 1,
+                    day,
+                    part
+                ));
+            }
+        }
+
+        Ok(Manifest { expected })
+    }
+
+    pub fn expected(&self, day: u32, part: u32) -> Option<&str> {
+        self.expected.get(&(day, part)).map(String::as_str)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail {
+        expected: String,
+        actual: Option<String>,
+    },
+    Error(String),
+    MissingInput,
+    MissingAnswer,
+}
+
+impl Verdict {
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Verdict::Fail { .. } | Verdict::Error(_))
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "pass"),
+            Verdict::Fail {
+                expected,
+                actual: Some(actual),
+            } => write!(f, "FAIL (expected {}, got {})", expected, actual),
+            Verdict::Fail {
+                expected,
+                actual: None,
+            } => write!(f, "FAIL (expected {}, found no answer)", expected),
+            Verdict::Error(e) => write!(f, "FAIL ({})", e),
+            Verdict::MissingInput => write!(f, "missing input"),
+            Verdict::MissingAnswer => write!(f, "missing expected answer"),
+        }
+    }
+}
+
+/// Runs both parts of `day` against `input` and checks them against the manifest.
+///
+/// `input` is None when the day's input file isn't available.
+pub fn verify(day: &Day, manifest: &Manifest, input: Option<&str>) -> Vec<(u32, Verdict)> {
+    let parts = [1, 2];
+    let input = match input {
+        Some(input) => input,
+        None => return parts.iter().map(|&p| (p, Verdict::MissingInput)).collect(),
+    };
+
+    let answers = match day.run(input, Part::All) {
+        Ok(run) => run.parts,
+        Err(e) => {
+            return parts
+                .iter()
+                .map(|&p| (p, Verdict::Error(e.to_string())))
+                .collect()
+        }
+    };
+
+    answers
+        .into_iter()
+        .map(|answer| {
+            let verdict = match manifest.expected(day.number, answer.part) {
+                None => Verdict::MissingAnswer,
+                Some(expected) if answer.answer.as_deref() == Some(expected) => Verdict::Pass,
+                Some(expected) => Verdict::Fail {
+                    expected: expected.to_string(),
+                    actual: answer.answer,
+                },
+            };
+            (answer.part, verdict)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn manifest_test() {
+        let manifest = Manifest::parse("# day part answer\n\n1 1 514579\n1 2 241861950\n").unwrap();
+        assert_eq!(manifest.expected(1, 1), Some("514579"));
+        assert_eq!(manifest.expected(1, 2), Some("241861950"));
+        assert_eq!(manifest.expected(2, 1), None);
+
+        assert!(Manifest::parse("1 1").is_err());
+        assert!(Manifest::parse("1 1 5\n1 1 6").is_err());
+    }
+
+    #[test]
+    fn verify_test() {
+        let day = crate::runner::get_day(1).unwrap();
+        let manifest = Manifest::parse("1 1 514579\n1 2 1").unwrap();
+        let input = "1721\n979\n366\n299\n675\n1456\n";
+
+        assert_eq!(
+            verify(day, &manifest, Some(input)),
+            vec![
+                (1, Verdict::Pass),
+                (
+                    2,
+                    Verdict::Fail {
+                        expected: "1".to_string(),
+                        actual: Some("241861950".to_string())
+                    }
+                )
+            ]
+        );
+        assert_eq!(
+            verify(day, &Manifest::default(), Some(input)),
+            vec![(1, Verdict::MissingAnswer), (2, Verdict::MissingAnswer)]
+        );
+        assert_eq!(
+            verify(day, &manifest, None),
+            vec![(1, Verdict::MissingInput), (2, Verdict::MissingInput)]
+        );
+    }
+}
@@ -0,0 +1,52 @@
+//! Checks every registered day against the expected answers in answers.txt.
+//!
+//! Puzzle inputs aren't checked in, so a day whose inputs/dayNN.txt is absent is skipped.
+
+use std::path::Path;
+
+use aoc2020::input;
+use aoc2020::runner::{self, Day};
+use aoc2020::verify::{self, Manifest, Verdict};
+
+/// Each way `day`'s answers differ from `manifest`
+fn check_day(root: &Path, manifest: &Manifest, day: &Day) -> Vec<String> {
+    let input = match input::read(&root.join(day.default_input_path())) {
+        Ok(input) => input,
+        Err(_) => {
+            eprintln!(
+                "Skipping day {}: {} not found",
+                day.number,
+                day.default_input_path().display()
+            );
+            return Vec::new();
+        }
+    };
+
+    let mut failures = Vec::new();
+    for (part, verdict) in verify::verify(day, manifest, Some(&input)) {
+        match verdict {
+            Verdict::Pass => {}
+            Verdict::MissingAnswer => {
+                eprintln!(
+                    "Skipping day {} part {}: no expected answer",
+                    day.number, part
+                )
+            }
+            verdict => failures.push(format!("Day {} part {}: {}", day.number, part, verdict)),
+        }
+    }
+    failures
+}
+
+#[test]
+fn answers() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let manifest = std::fs::read_to_string(root.join("answers.txt")).unwrap();
+    let manifest = Manifest::parse(&manifest).unwrap();
+
+    let failures = runner::DAYS
+        .iter()
+        .flat_map(|day| check_day(root, &manifest, day))
+        .collect::<Vec<_>>();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}